@@ -0,0 +1,78 @@
+//
+// Compare ways of finding the largest bucket for a guess, as used by
+// find_greedy_worst_case: the library's own, a HashMap of counts, a
+// fixed array of counts, and a fixed array over a flat score cache.
+//
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use std::collections::HashMap;
+
+use wordle_solver::{num_buckets, BucketId, Scorer, WORD_LEN};
+
+// The default word lists are Wordle's.
+const MAX_BUCKET: usize = num_buckets(WORD_LEN);
+
+// Enough guesses to be representative, without taking forever.
+const NUM_GUESSES: usize = 500;
+
+fn worst_case_hashmap(s: &Scorer, guess: usize, answers: &[usize]) -> usize {
+    let mut counts = HashMap::new();
+    for answer in answers.iter() {
+        *counts.entry(s.score_cache[guess][*answer]).or_insert(0) += 1;
+    }
+    counts.into_values().max().unwrap_or(0)
+}
+
+fn worst_case_array(s: &Scorer, guess: usize, answers: &[usize]) -> usize {
+    let mut counts = [0; MAX_BUCKET];
+    for answer in answers.iter() {
+        counts[s.score_cache[guess][*answer] as usize] += 1;
+    }
+    counts.iter().copied().max().unwrap_or(0)
+}
+
+fn worst_case_flat(flat: &[BucketId], num_answers: usize, guess: usize, answers: &[usize]) -> usize {
+    let row = &flat[guess * num_answers..(guess + 1) * num_answers];
+    let mut counts = [0; MAX_BUCKET];
+    for answer in answers.iter() {
+        counts[row[*answer] as usize] += 1;
+    }
+    counts.iter().copied().max().unwrap_or(0)
+}
+
+fn bench_bucketing(c: &mut Criterion) {
+    let s = match Scorer::new() {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("Skipping bucketing benchmarks: {}", e);
+            return;
+        }
+    };
+    let answers = (0..s.answers.len()).collect::<Vec<_>>();
+    let guesses = 0..NUM_GUESSES.min(s.guesses.len());
+    let flat = s.score_cache.concat();
+
+    let mut group = c.benchmark_group("find_greedy_worst_case");
+    group.bench_function("library", |b| {
+        b.iter(|| guesses.clone().map(|g| s.find_greedy_worst_case(g, black_box(&answers))).max())
+    });
+    group.bench_function("hashmap", |b| {
+        b.iter(|| guesses.clone().map(|g| worst_case_hashmap(&s, g, black_box(&answers))).max())
+    });
+    group.bench_function("array", |b| {
+        b.iter(|| guesses.clone().map(|g| worst_case_array(&s, g, black_box(&answers))).max())
+    });
+    group.bench_function("flat", |b| {
+        b.iter(|| {
+            guesses
+                .clone()
+                .map(|g| worst_case_flat(&flat, s.answers.len(), g, black_box(&answers)))
+                .max()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_bucketing);
+criterion_main!(benches);
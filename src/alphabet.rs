@@ -0,0 +1,95 @@
+//
+// The letters words are made of, for sizing per-letter tables. English
+// words use a-z, but other languages and variants have more or fewer.
+//
+
+const NOT_A_LETTER: u8 = u8::MAX;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Alphabet {
+    // The letters, in increasing order.
+    letters: Vec<u8>,
+    // Index into 'letters' of each byte, or NOT_A_LETTER.
+    index: [u8; 256],
+}
+
+impl Alphabet {
+    // The letters, in any order, and ignoring duplicates.
+    pub fn new(letters: &[u8]) -> Alphabet {
+        let mut letters = letters.to_vec();
+        letters.sort_unstable();
+        letters.dedup();
+        assert!(letters.len() < NOT_A_LETTER as usize, "Too many letters");
+        let mut index = [NOT_A_LETTER; 256];
+        for (idx, c) in letters.iter().enumerate() {
+            index[*c as usize] = idx as u8;
+        }
+        Alphabet { letters, index }
+    }
+
+    pub fn english() -> Alphabet {
+        Alphabet::new(&(b'a'..=b'z').collect::<Vec<_>>())
+    }
+
+    // The letters used by some words.
+    pub fn from_words<'a>(words: impl Iterator<Item = &'a str>) -> Alphabet {
+        let mut used = [false; 256];
+        for word in words {
+            for c in word.bytes() {
+                used[c as usize] = true;
+            }
+        }
+        let letters = (0..=255u8).filter(|c| used[*c as usize]).collect::<Vec<_>>();
+        Alphabet::new(&letters)
+    }
+
+    pub fn len(&self) -> usize {
+        self.letters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.letters.is_empty()
+    }
+
+    pub fn letters(&self) -> &[u8] {
+        &self.letters
+    }
+
+    pub fn contains(&self, c: u8) -> bool {
+        self.index[c as usize] != NOT_A_LETTER
+    }
+
+    // Position of the letter in the alphabet, for indexing tables of
+    // size len().
+    pub fn index(&self, c: u8) -> usize {
+        let idx = self.index[c as usize];
+        assert!(idx != NOT_A_LETTER, "'{}' is not in the alphabet", c as char);
+        idx as usize
+    }
+
+    pub fn letter(&self, idx: usize) -> u8 {
+        self.letters[idx]
+    }
+}
+
+impl Default for Alphabet {
+    fn default() -> Alphabet {
+        Alphabet::english()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_words() {
+        let alphabet = Alphabet::from_words(["cigar", "rebut"].iter().copied());
+        assert_eq!(alphabet.letters(), b"abcegirtu");
+        assert_eq!(alphabet.index(b'a'), 0);
+        assert_eq!(alphabet.index(b'u'), 8);
+        assert_eq!(alphabet.letter(3), b'e');
+        assert!(alphabet.contains(b'c') && !alphabet.contains(b'z'));
+        assert_eq!(Alphabet::english().len(), 26);
+    }
+}
@@ -0,0 +1,103 @@
+//
+// Playing several boards at once (Dordle, Quordle, Sedecordle...), where
+// each guess is scored against every board and there's a shared guess
+// budget.
+//
+
+use crate::{Rank, Ranking, Scorer};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BoardsOutcome {
+    // Guesses made, up to the budget.
+    pub guesses: usize,
+    // Boards whose answer was guessed.
+    pub solved: usize,
+}
+
+impl Scorer {
+    // Pick a guess for the boards not yet solved. If a board is down to
+    // one candidate, finish it off. Otherwise, greedily pick the guess
+    // with the best ranking summed over the boards.
+    fn best_boards_guess(&self, boards: &[Vec<usize>], ranking: Ranking) -> usize {
+        if let Some(board) = boards.iter().find(|b| b.len() == 1) {
+            return self.guess_index(&self.answers[board[0]]).unwrap();
+        }
+
+        (0..self.guesses.len())
+            .map(|guess| {
+                let rank = boards.iter().fold((0.0, 0), |acc: Rank, b| {
+                    let rank = self.rank_guess(ranking, guess, b);
+                    (acc.0 + rank.0, acc.1 + rank.1)
+                });
+                let is_candidate = boards
+                    .iter()
+                    .any(|b| b.iter().any(|a| self.answers[*a] == self.guesses[guess]));
+                (rank, !is_candidate, guess)
+            })
+            .min_by(|a, b| a.partial_cmp(b).unwrap())
+            .map(|(_, _, guess)| guess)
+            .unwrap()
+    }
+
+    // Play one board per target answer, all starting from the same
+    // candidates, within the given number of guesses. The first guess
+    // can be supplied, as it's the same for every game and expensive to
+    // find.
+    pub fn solve_boards(
+        &self,
+        targets: &[usize],
+        candidates: &[usize],
+        budget: usize,
+        ranking: Ranking,
+        opener: Option<usize>,
+    ) -> BoardsOutcome {
+        let mut boards = targets
+            .iter()
+            .map(|target| (*target, candidates.to_vec()))
+            .collect::<Vec<_>>();
+
+        let mut guesses = 0;
+        while !boards.is_empty() && guesses < budget {
+            let guess = match opener {
+                Some(opener) if guesses == 0 => opener,
+                _ => {
+                    let unsolved = boards.iter().map(|(_, b)| b.clone()).collect::<Vec<_>>();
+                    self.best_boards_guess(&unsolved, ranking)
+                }
+            };
+            guesses += 1;
+
+            // Solved boards drop out; the rest get narrowed.
+            boards.retain(|(target, _)| self.answers[*target] != self.guesses[guess]);
+            for (target, board) in boards.iter_mut() {
+                let score = self.score(guess, *target);
+                *board = self.filter_candidates_checked(guess, score, board, Some(*target)).unwrap();
+            }
+        }
+
+        BoardsOutcome {
+            guesses,
+            solved: targets.len() - boards.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::small_scorer;
+
+    #[test]
+    fn test_solve_boards() {
+        let s = small_scorer(&["zzzbc"], &["aaaaa", "aaaab", "aaaac"]);
+        let all = [0, 1, 2];
+
+        // "zzzbc" identifies both, then each needs its own guess.
+        let outcome = s.solve_boards(&[0, 2], &all, 10, Ranking::WorstCase, None);
+        assert_eq!(outcome, BoardsOutcome { guesses: 3, solved: 2 });
+
+        // Not enough budget.
+        let outcome = s.solve_boards(&[0, 2], &all, 2, Ranking::WorstCase, None);
+        assert_eq!(outcome, BoardsOutcome { guesses: 2, solved: 1 });
+    }
+}
@@ -0,0 +1,255 @@
+//
+// Explicit constraints on the answer, accumulated from feedback, as an
+// alternative to filtering candidates by score. Words are assumed to
+// be five letters of lower-case ASCII unless another length and
+// alphabet are given.
+//
+
+use crate::{decode_score, Alphabet, BucketId, CharScore, WORD_LEN};
+
+// The exclusions are a bitmask per position.
+pub(crate) const MAX_LETTERS: usize = 64;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Constraints {
+    alphabet: Alphabet,
+    // Letters known to be in each position.
+    fixed: Vec<Option<u8>>,
+    // Bitmask per position of letters known not to be there.
+    excluded: Vec<u64>,
+    // Fewest times each letter must appear, indexed by the alphabet.
+    pub min_count: Vec<u8>,
+    // Most times each letter may appear.
+    pub max_count: Vec<u8>,
+}
+
+impl Default for Constraints {
+    fn default() -> Constraints {
+        Constraints::with_alphabet(&Alphabet::english(), WORD_LEN)
+    }
+}
+
+impl Constraints {
+    pub fn new() -> Constraints {
+        Constraints::default()
+    }
+
+    // For 'word_len'-letter words over the alphabet.
+    pub fn with_alphabet(alphabet: &Alphabet, word_len: usize) -> Constraints {
+        assert!(alphabet.len() <= MAX_LETTERS, "Alphabet too large for constraints");
+        Constraints {
+            alphabet: alphabet.clone(),
+            fixed: vec![None; word_len],
+            excluded: vec![0; word_len],
+            min_count: vec![0; alphabet.len()],
+            max_count: vec![word_len as u8; alphabet.len()],
+        }
+    }
+
+    // Add what the feedback for a guess tells us.
+    pub fn add(&mut self, guess: &[u8], score: BucketId) {
+        assert_eq!(guess.len(), self.fixed.len());
+        let scores = decode_score(score, guess.len());
+
+        // Each green or yellow is a distinct occurrence of the letter
+        // in the answer. A grey as well means there are no more.
+        let num_letters = self.alphabet.len();
+        let mut seen = vec![0u8; num_letters];
+        let mut capped = vec![false; num_letters];
+        for (idx, (c, cs)) in guess.iter().zip(scores.iter()).enumerate() {
+            let letter = self.alphabet.index(*c);
+            match cs {
+                CharScore::Correct => {
+                    self.fixed[idx] = Some(*c);
+                    seen[letter] += 1;
+                }
+                CharScore::Present => {
+                    self.excluded[idx] |= 1 << letter;
+                    seen[letter] += 1;
+                }
+                CharScore::Absent => {
+                    self.excluded[idx] |= 1 << letter;
+                    capped[letter] = true;
+                }
+            }
+        }
+
+        for letter in 0..num_letters {
+            self.min_count[letter] = self.min_count[letter].max(seen[letter]);
+            if capped[letter] {
+                self.max_count[letter] = self.max_count[letter].min(seen[letter]);
+            }
+        }
+    }
+
+    // Could the word be the answer?
+    pub fn allows(&self, word: &[u8]) -> bool {
+        assert_eq!(word.len(), self.fixed.len());
+        let mut counts = vec![0u8; self.alphabet.len()];
+        for (idx, c) in word.iter().enumerate() {
+            let letter = self.alphabet.index(*c);
+            if self.fixed[idx].is_some_and(|f| f != *c) || self.excluded[idx] & (1 << letter) != 0 {
+                return false;
+            }
+            counts[letter] += 1;
+        }
+        counts
+            .iter()
+            .zip(self.min_count.iter().zip(self.max_count.iter()))
+            .all(|(count, (min, max))| min <= count && count <= max)
+    }
+
+    // Could the word be guessed in hard mode? It must have each letter
+    // known to be in place there, and each letter known to be present
+    // at least as many times as it's known to be, greens included, so
+    // a green covers a yellow for the same letter in an earlier guess.
+    pub fn allows_in_hard_mode(&self, word: &[u8]) -> bool {
+        assert_eq!(word.len(), self.fixed.len());
+        let mut counts = vec![0u8; self.alphabet.len()];
+        for (idx, c) in word.iter().enumerate() {
+            if self.fixed[idx].is_some_and(|f| f != *c) {
+                return false;
+            }
+            counts[self.alphabet.index(*c)] += 1;
+        }
+        counts.iter().zip(self.min_count.iter()).all(|(count, min)| min <= count)
+    }
+
+    // A summary of what's known, a line per position, giving the letter
+    // there or those ruled out (other than those absent altogether),
+    // then the letters known to be present (with how many, if more
+    // than one) and those absent.
+    pub fn board(&self) -> String {
+        let letters = |pred: &dyn Fn(usize) -> bool| {
+            (0..self.alphabet.len())
+                .filter(|letter| pred(*letter))
+                .map(|letter| self.alphabet.letter(letter) as char)
+                .collect::<Vec<_>>()
+        };
+        let mut lines = (0..self.fixed.len())
+            .map(|idx| match self.fixed[idx] {
+                Some(c) => format!("{}: {}", idx + 1, c as char),
+                None => {
+                    let excluded =
+                        letters(&|letter| self.excluded[idx] & (1 << letter) != 0 && self.max_count[letter] > 0);
+                    if excluded.is_empty() {
+                        format!("{}: ?", idx + 1)
+                    } else {
+                        format!("{}: not {}", idx + 1, excluded.iter().collect::<String>())
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+        let present = (0..self.alphabet.len())
+            .filter(|letter| self.min_count[*letter] > 0)
+            .map(|letter| match self.min_count[letter] {
+                1 => (self.alphabet.letter(letter) as char).to_string(),
+                n => format!("{}x{}", self.alphabet.letter(letter) as char, n),
+            })
+            .collect::<Vec<_>>();
+        lines.push(format!("Present: {}", present.join(" ")));
+        lines.push(format!("Absent: {}", letters(&|letter| self.max_count[letter] == 0).iter().collect::<String>()));
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_score, score_wordle};
+
+    #[test]
+    fn test_repeated_yellow() {
+        // Two yellow E's and a grey one: exactly two E's, neither at the
+        // start nor the end.
+        let mut constraints = Constraints::new();
+        constraints.add(b"eerie", parse_score("yyaaa", WORD_LEN).unwrap());
+        let e = Alphabet::english().index(b'e');
+        assert_eq!(constraints.min_count[e], 2);
+        assert_eq!(constraints.max_count[e], 2);
+
+        assert!(constraints.allows(b"sheep"));
+        assert!(constraints.allows(b"sheen"));
+        // Only one E.
+        assert!(!constraints.allows(b"cheap"));
+        // Three E's.
+        assert!(!constraints.allows(b"tepee"));
+        // E in a position ruled out.
+        assert!(!constraints.allows(b"theme"));
+    }
+
+    #[test]
+    fn test_grey_duplicate() {
+        // Against "there", the second E is grey but the others aren't.
+        // That grey only says there's no third E, not that there's none.
+        let mut constraints = Constraints::new();
+        constraints.add(b"eerie", parse_score("yayag", WORD_LEN).unwrap());
+        let e = Alphabet::english().index(b'e');
+        assert_eq!(constraints.min_count[e], 2);
+        assert_eq!(constraints.max_count[e], 2);
+
+        assert!(constraints.allows(b"there"));
+        assert!(constraints.allows(b"where"));
+        // But an E can't be where the grey one was.
+        assert!(!constraints.allows(b"genre"));
+        // And there are still two E's.
+        assert!(!constraints.allows(b"route"));
+    }
+
+    #[test]
+    fn test_matches_scoring() {
+        let words = ["sheep", "eerie", "there", "geese", "speed", "abide", "elder", "cheap", "tepee"];
+        for guess in words.iter() {
+            for answer in words.iter() {
+                let mut constraints = Constraints::new();
+                constraints.add(guess.as_bytes(), score_wordle(guess.as_bytes(), answer.as_bytes()));
+                for word in words.iter() {
+                    let same_score = score_wordle(guess.as_bytes(), word.as_bytes())
+                        == score_wordle(guess.as_bytes(), answer.as_bytes());
+                    assert_eq!(constraints.allows(word.as_bytes()), same_score, "{} {} {}", guess, answer, word);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_allows_in_hard_mode() {
+        // Against "there": at least two E's, one of them at the end, and
+        // an R. Greys, and yellows' positions, don't matter.
+        let mut constraints = Constraints::new();
+        constraints.add(b"eerie", parse_score("yayag", WORD_LEN).unwrap());
+        assert!(constraints.allows_in_hard_mode(b"there"));
+        assert!(constraints.allows_in_hard_mode(b"eerie"));
+        assert!(constraints.allows_in_hard_mode(b"genre"));
+        assert!(!constraints.allows_in_hard_mode(b"route"));
+        assert!(!constraints.allows_in_hard_mode(b"lever"));
+
+        // A later green E doesn't add to the count, and the T is fixed.
+        constraints.add(b"theme", parse_score("gaaag", WORD_LEN).unwrap());
+        assert!(constraints.allows_in_hard_mode(b"three"));
+        assert!(!constraints.allows_in_hard_mode(b"where"));
+    }
+
+    #[test]
+    fn test_board() {
+        let mut constraints = Constraints::new();
+        constraints.add(b"eerie", parse_score("yayag", WORD_LEN).unwrap());
+        constraints.add(b"theme", parse_score("gaaag", WORD_LEN).unwrap());
+        assert_eq!(
+            constraints.board(),
+            "1: t\n2: not e\n3: not er\n4: ?\n5: e\nPresent: ex2 r t\nAbsent: him"
+        );
+    }
+
+    #[test]
+    fn test_small_alphabet() {
+        let alphabet = Alphabet::new(b"abcd");
+        let mut constraints = Constraints::with_alphabet(&alphabet, WORD_LEN);
+        constraints.add(b"abcac", parse_score("gaaya", WORD_LEN).unwrap());
+        assert_eq!(constraints.min_count, vec![2, 0, 0, 0]);
+        assert_eq!(constraints.max_count, vec![WORD_LEN as u8, 0, 0, WORD_LEN as u8]);
+        assert!(constraints.allows(b"adada"));
+        // An A where the yellow was.
+        assert!(!constraints.allows(b"adaad"));
+    }
+}
@@ -0,0 +1,167 @@
+//
+// State of a game in progress, for playing along with a real game
+//
+
+use crate::{BucketId, Constraints, Oracle, Ranking, Scorer, WordleError};
+
+pub struct GameState {
+    // Answers still consistent with the feedback so far.
+    candidates: Vec<usize>,
+    // The (guess, score) pairs applied so far.
+    history: Vec<(usize, BucketId)>,
+    // The candidates before each entry in the history, for undo.
+    previous: Vec<Vec<usize>>,
+}
+
+impl GameState {
+    pub fn new(candidates: Vec<usize>) -> GameState {
+        GameState {
+            candidates,
+            history: Vec::new(),
+            previous: Vec::new(),
+        }
+    }
+
+    pub fn candidates(&self) -> &[usize] {
+        &self.candidates
+    }
+
+    pub fn history(&self) -> &[(usize, BucketId)] {
+        &self.history
+    }
+
+    // Has the answer been guessed?
+    pub fn is_solved(&self, s: &Scorer) -> bool {
+        self.history.last().is_some_and(|(_, score)| *score == s.correct_score())
+    }
+
+    // The guesses made so far.
+    pub fn guessed(&self) -> Vec<usize> {
+        self.history.iter().map(|(guess, _)| *guess).collect()
+    }
+
+    // The guesses not to suggest: those made so far, and in hard mode,
+    // those the feedback so far rules out.
+    pub fn excluded(&self, s: &Scorer) -> Vec<usize> {
+        if s.hard_mode {
+            s.hard_mode_excluded(&self.history)
+        } else {
+            self.guessed()
+        }
+    }
+
+    // What the feedback so far says about the answer, letter by letter.
+    pub fn constraints(&self, s: &Scorer) -> Constraints {
+        let mut constraints = Constraints::with_alphabet(&s.alphabet, s.word_len);
+        for (guess, score) in self.history.iter() {
+            constraints.add(s.guesses[*guess].as_bytes(), *score);
+        }
+        constraints
+    }
+
+    // The best guess to make next. Guesses already made are never
+    // suggested again, as their feedback is already known. In hard mode,
+    // only guesses the feedback so far allows are suggested.
+    pub fn suggest(&self, s: &Scorer, ranking: Ranking) -> Option<usize> {
+        if s.hard_mode {
+            return s.best_hard_mode_guess(&self.candidates, ranking, &self.history);
+        }
+        s.best_guess_excluding(&self.candidates, ranking, &self.guessed())
+    }
+
+    // Narrow the candidates with the feedback for a guess. Feedback that
+    // no candidate could produce is rejected, leaving the state as it
+    // was.
+    pub fn apply(&mut self, s: &Scorer, guess: usize, score: BucketId) -> Result<(), WordleError> {
+        let candidates = s.filter_candidates_checked(guess, score, &self.candidates, None)?;
+        self.previous.push(std::mem::replace(&mut self.candidates, candidates));
+        self.history.push((guess, score));
+        Ok(())
+    }
+
+    // Roll back the last applied feedback, returning false if there is
+    // nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        match self.previous.pop() {
+            Some(candidates) => {
+                self.candidates = candidates;
+                self.history.pop();
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Play the rest of the game, making the suggested guesses and
+    // applying the oracle's feedback, until solved.
+    pub fn play(&mut self, s: &Scorer, ranking: Ranking, oracle: &mut dyn Oracle) -> Result<(), WordleError> {
+        while !self.is_solved(s) {
+            let guess = self.suggest(s, ranking).ok_or_else(|| WordleError::InvalidInput(String::from(
+                "No candidate answers remain",
+            )))?;
+            let score = oracle.score(&s.guesses[guess])?;
+            self.apply(s, guess, score)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::small_scorer;
+    use crate::{parse_score, KnownAnswer, ScoringRules, WORD_LEN};
+
+    #[test]
+    fn test_apply() {
+        let s = small_scorer(&["zzzzb"], &["aaaaa", "aaaab", "aaaac"]);
+        let mut state = GameState::new(vec![0, 1, 2]);
+
+        state.apply(&s, 0, parse_score("aaaaa", WORD_LEN).unwrap()).unwrap();
+        assert_eq!(state.candidates(), &[0, 2]);
+        assert!(!state.is_solved(&s));
+
+        // "aaaab" has been ruled out.
+        assert!(state.apply(&s, 0, parse_score("aaaag", WORD_LEN).unwrap()).is_err());
+        assert_eq!(state.candidates(), &[0, 2]);
+        assert_eq!(state.history().len(), 1);
+
+        state.apply(&s, 3, s.correct_score()).unwrap();
+        assert_eq!(state.candidates(), &[2]);
+        assert!(state.is_solved(&s));
+
+        assert!(state.undo());
+        assert_eq!(state.candidates(), &[0, 2]);
+        assert!(state.undo());
+        assert_eq!(state.candidates(), &[0, 1, 2]);
+        assert!(state.history().is_empty());
+        assert!(!state.undo());
+    }
+
+    #[test]
+    fn test_play() {
+        let s = small_scorer(&["zzzbc"], &["aaaaa", "aaaab", "aaaac"]);
+
+        let mut state = GameState::new(vec![0, 1, 2]);
+        let mut oracle = KnownAnswer::new("aaaac", ScoringRules::Standard);
+        state.play(&s, Ranking::WorstCase, &mut oracle).unwrap();
+        assert_eq!(state.history().iter().map(|(guess, _)| *guess).collect::<Vec<_>>(), vec![0, 3]);
+
+        // Feedback no candidate gives is an error.
+        let mut state = GameState::new(vec![0, 1, 2]);
+        let mut oracle = KnownAnswer::new("zzzzz", ScoringRules::Standard);
+        assert!(state.play(&s, Ranking::WorstCase, &mut oracle).is_err());
+    }
+
+    #[test]
+    fn test_no_repeated_suggestion() {
+        let s = small_scorer(&["zzzbc"], &["aaaaa", "aaaab", "aaaac", "aaaad"]);
+
+        let mut state = GameState::new(vec![0, 1, 2, 3]);
+        let first = state.suggest(&s, Ranking::WorstCase).unwrap();
+        state.apply(&s, first, parse_score("aaaaa", WORD_LEN).unwrap()).unwrap();
+        assert_eq!(state.guessed(), vec![first]);
+        let second = state.suggest(&s, Ranking::WorstCase).unwrap();
+        assert_ne!(second, first);
+    }
+}
@@ -0,0 +1,265 @@
+//
+// Interactive mode: suggest guesses and read back the feedback from a
+// real game.
+//
+
+use std::io::{self, BufRead, Write};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use wordle_solver::{parse_score, BucketId, GameState, Pattern, Ranking, Scorer, WordleError};
+
+use crate::list_words;
+
+// How many guesses to show with --assist.
+const TOP_GUESSES: usize = 5;
+
+// A line of input from the user.
+enum Command {
+    // The guess made, and its feedback.
+    Feedback(usize, BucketId),
+    // Take back the last feedback.
+    Undo,
+    // Show how good a guess would be.
+    Why(usize),
+    // Show what's known about each letter.
+    Board,
+    // List the candidates with all these letters, as many times as
+    // given.
+    Has(String),
+    // List the candidates matching the pattern.
+    Like(Box<Pattern>),
+    // Ask for the answer, which only --practice knows.
+    GiveUp,
+}
+
+// Read and parse a line of input: "undo", "why <guess>", "board",
+// "has <letters>", "like <pattern>", "give-up", or feedback either on
+// its own, for the suggested guess, or preceded by the word actually
+// guessed. Returns None on end of input.
+fn read_command(
+    s: &Scorer,
+    input: &mut impl BufRead,
+    suggestion: usize,
+) -> io::Result<Option<Result<Command, WordleError>>> {
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+
+    let lookup = |word: &str| {
+        let word = word.to_ascii_lowercase();
+        s.guess_index(&word).ok_or(WordleError::UnknownGuess { word })
+    };
+    let words = line.split_whitespace().collect::<Vec<_>>();
+    let (guess, feedback) = match words.as_slice() {
+        ["undo"] => return Ok(Some(Ok(Command::Undo))),
+        ["board"] => return Ok(Some(Ok(Command::Board))),
+        ["has", letters] => return Ok(Some(Ok(Command::Has(letters.to_ascii_lowercase())))),
+        ["like", pattern] => return Ok(Some(Pattern::parse(pattern, &s.alphabet).map(|p| Command::Like(Box::new(p))))),
+        ["give-up"] => return Ok(Some(Ok(Command::GiveUp))),
+        ["why", word] => return Ok(Some(lookup(word).map(Command::Why))),
+        [feedback] => (suggestion, *feedback),
+        [word, feedback] => match lookup(word) {
+            Ok(guess) => (guess, *feedback),
+            Err(e) => return Ok(Some(Err(e))),
+        },
+        _ => {
+            return Ok(Some(Err(WordleError::InvalidInput(String::from(
+                "Expected '<feedback>' or '<guess> <feedback>'",
+            )))))
+        }
+    };
+    Ok(Some(parse_score(feedback, s.word_len).map(|score| Command::Feedback(guess, score))))
+}
+
+// Show the other guesses that are just as good as the suggestion, and
+// could be made next.
+fn print_alternatives(s: &Scorer, state: &GameState, ranking: Ranking, suggestion: usize, max_print: usize) {
+    let others = s
+        .best_guesses_excluding(state.candidates(), ranking, &state.excluded(s))
+        .into_iter()
+        .filter(|guess| *guess != suggestion)
+        .map(|guess| s.guesses[guess].as_str())
+        .collect::<Vec<_>>();
+    if others.is_empty() {
+        return;
+    }
+    println!("Equally good: {}", list_words(&others, ", ", max_print));
+}
+
+// Show how much uncertainty is left, and how much the suggested guess
+// will remove on average, compared to the best possible.
+fn print_entropy(s: &Scorer, candidates: &[usize], suggestion: usize) {
+    let (best, best_bits) = s.find_max_entropy(candidates).unwrap();
+    println!(
+        "Uncertainty: {:.2} bits. This guess removes {:.2} bits on average (best is {:.2}, with {}).",
+        (candidates.len() as f64).log2(),
+        s.entropy(suggestion, candidates),
+        best_bits,
+        s.guesses[best]
+    );
+}
+
+// Show how a guess of the user's own would split the candidates,
+// alongside the suggestion.
+fn print_why(s: &Scorer, candidates: &[usize], guess: usize, suggestion: usize) {
+    for (label, word) in [("Yours", guess), ("Suggested", suggestion)] {
+        println!(
+            "{:>9}: {}: largest bucket {}, {} buckets, {:.2} candidates expected to remain",
+            label,
+            s.guesses[word],
+            s.find_greedy_worst_case(word, candidates),
+            s.bucket_count(word, candidates),
+            s.expected_remaining(word, candidates)
+        );
+    }
+}
+
+// Show the best few guesses with all their metrics, so the user can
+// pick for themselves.
+fn print_top_guesses(s: &Scorer, candidates: &[usize]) {
+    println!("{:>5}  {:>7}  {:>7}  {:>8}", "Guess", "Largest", "Buckets", "Expected");
+    for eval in s.top_guesses(candidates, TOP_GUESSES) {
+        println!(
+            "{:>5}  {:>7}  {:>7}  {:>8.2}",
+            eval.word, eval.worst_case, eval.num_buckets, eval.expected_remaining
+        );
+    }
+}
+
+// List the candidates with all the letters, as many times as they're
+// given, so "has ee" finds those with at least two E's.
+fn print_has(s: &Scorer, candidates: &[usize], letters: &str, max_print: usize) {
+    let matches = candidates
+        .iter()
+        .map(|a| s.answers[*a].as_str())
+        .filter(|word| {
+            letters
+                .bytes()
+                .all(|c| word.bytes().filter(|w| *w == c).count() >= letters.bytes().filter(|l| *l == c).count())
+        })
+        .collect::<Vec<_>>();
+    print_matches(&matches, max_print);
+}
+
+// List the candidates matching the pattern.
+fn print_like(s: &Scorer, candidates: &[usize], pattern: &Pattern, max_print: usize) {
+    if pattern.word_len() != s.word_len {
+        println!("The pattern should be {} letters long", s.word_len);
+        return;
+    }
+    let matches = candidates
+        .iter()
+        .map(|a| s.answers[*a].as_str())
+        .filter(|word| pattern.matches(word.as_bytes()))
+        .collect::<Vec<_>>();
+    print_matches(&matches, max_print);
+}
+
+fn print_matches(matches: &[&str], max_print: usize) {
+    println!("{} candidates: {}", matches.len(), list_words(matches, ", ", max_print));
+}
+
+fn goodbye(remaining: usize) {
+    println!("Goodbye! {} candidate answers remained.", remaining);
+}
+
+// The opener, if given, is suggested first. If 'explain' is set, justify
+// each suggestion. If 'assist' is set, show the top few guesses
+// alongside it.
+pub fn run(
+    s: &Scorer,
+    ranking: Ranking,
+    opener: Option<usize>,
+    candidates: Vec<usize>,
+    max_print: usize,
+    explain: bool,
+    assist: bool,
+) {
+    let mut state = GameState::new(candidates);
+
+    // Let Ctrl-C end the session as cleanly as end of input does.
+    let remaining = Arc::new(AtomicUsize::new(state.candidates().len()));
+    let handler_remaining = remaining.clone();
+    ctrlc::set_handler(move || {
+        println!();
+        goodbye(handler_remaining.load(Ordering::SeqCst));
+        process::exit(0);
+    })
+    .expect("Could not set Ctrl-C handler");
+
+    println!("Enter feedback with a letter per position: g(reen), y(ellow) or a(bsent).");
+    println!("C(orrect) and p(resent) work too, so 'aacpc' is 'aagyg'.");
+    println!("If you guessed a different word, enter '<guess> <feedback>'.");
+    println!("Enter 'undo' to take back the last feedback.");
+    println!("Enter 'why <guess>' to compare a guess of your own with the suggestion.");
+    println!("Enter 'board' to see what's known about each position.");
+    println!("Enter 'has <letters>' or 'like <pattern>' (e.g. '.ight') to list matching candidates.");
+
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    while !state.is_solved(s) {
+        if let [answer] = state.candidates() {
+            println!("The answer must be '{}'.", s.answers[*answer]);
+            return;
+        }
+
+        let suggestion = match opener {
+            Some(opener) if state.history().is_empty() => opener,
+            _ => state.suggest(s, ranking).unwrap(),
+        };
+        println!(
+            "Suggested guess: {} ({} candidates remain)",
+            s.guesses[suggestion],
+            state.candidates().len()
+        );
+        if explain {
+            println!("It {}.", s.explain_guess(suggestion, state.candidates()));
+        }
+        if assist {
+            print_top_guesses(s, state.candidates());
+        }
+        print_alternatives(s, &state, ranking, suggestion, max_print);
+        print_entropy(s, state.candidates(), suggestion);
+        print!("> ");
+        io::stdout().flush().unwrap();
+
+        match read_command(s, &mut input, suggestion) {
+            Ok(Some(Ok(Command::Feedback(guess, score)))) => {
+                if let Err(e) = state.apply(s, guess, score) {
+                    println!("{}", e);
+                }
+                remaining.store(state.candidates().len(), Ordering::SeqCst);
+            }
+            Ok(Some(Ok(Command::Undo))) => {
+                if state.undo() {
+                    println!("Undone: {} candidates remain", state.candidates().len());
+                } else {
+                    println!("Nothing to undo");
+                }
+                remaining.store(state.candidates().len(), Ordering::SeqCst);
+            }
+            Ok(Some(Ok(Command::Why(guess)))) => print_why(s, state.candidates(), guess, suggestion),
+            Ok(Some(Ok(Command::Board))) => println!("{}", state.constraints(s).board()),
+            Ok(Some(Ok(Command::Has(letters)))) => print_has(s, state.candidates(), &letters, max_print),
+            Ok(Some(Ok(Command::Like(pattern)))) => print_like(s, state.candidates(), &pattern, max_print),
+            Ok(Some(Ok(Command::GiveUp))) => {
+                println!("The answer is only known in --practice mode. Here, it's up to your game.")
+            }
+            Ok(Some(Err(e))) => println!("{}", e),
+            Ok(None) => {
+                println!();
+                goodbye(state.candidates().len());
+                return;
+            }
+            Err(e) => {
+                eprintln!("Error reading input: {}", e);
+                goodbye(state.candidates().len());
+                process::exit(crate::EXIT_ERROR);
+            }
+        }
+    }
+    println!("Solved in {} guesses!", state.history().len());
+}
@@ -0,0 +1,3863 @@
+//
+// Wordle solver library
+//
+
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use rayon::prelude::*;
+
+mod alphabet;
+mod boards;
+mod constraints;
+mod game;
+mod metrics;
+mod optimal;
+mod oracle;
+mod practice;
+mod rng;
+mod server;
+mod signature;
+mod strategy;
+
+pub use alphabet::Alphabet;
+pub use boards::BoardsOutcome;
+pub use constraints::Constraints;
+pub use game::GameState;
+pub use metrics::{GuessMetrics, MetricRank, OpenerRanks};
+pub use optimal::OptimalSolver;
+#[cfg(feature = "http")]
+pub use oracle::HttpOracle;
+pub use oracle::{Adversary, KnownAnswer, NoisyOracle, Oracle, ReaderOracle};
+pub use practice::practice;
+pub use rng::Rng;
+pub use server::serve;
+pub use signature::{candidate_signature, word_list_signature, CandidateBitset};
+pub use strategy::Strategy;
+
+// The length of Wordle's own words. The Scorer takes its word length
+// from the word lists, so variants with other lengths work too.
+pub const WORD_LEN: usize = 5;
+
+// Scores are stored as u16s, which fit 3^10 scores, so that's the
+// longest word the Scorer handles. score_generic handles longer words,
+// without the score cache.
+pub const MAX_WORD_LEN: usize = 10;
+
+pub type BucketId = u16;
+
+// The number of distinct scores for words of the given length.
+pub const fn num_buckets(len: usize) -> usize {
+    3usize.pow(len as u32)
+}
+
+// Counting answers per score is quickest in an array, but longer words
+// have too many scores for that, so above this many, counts go in a map.
+pub(crate) const MAX_DENSE_BUCKETS: usize = num_buckets(6);
+
+////////////////////////////////////////////////////////////////////////
+// Core scoring/classification algorithm
+//
+
+// Result of a guessed letter, as determined by Wordle
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum CharScore {
+    Absent,
+    Correct,
+    Present,
+}
+
+// Compactly encode an arry of CharScores. Assumes the word is at most
+// MAX_WORD_LEN long.
+pub fn encode_score(cs: impl Iterator<Item = CharScore>) -> BucketId {
+    cs.map(|c| c as BucketId).fold(0, |acc, c| acc * 3 + c)
+}
+
+// As encode_score, for words up to MAX_GENERIC_LEN long.
+pub fn encode_score_wide(cs: impl Iterator<Item = CharScore>) -> u64 {
+    cs.map(|c| c as u64).fold(0, |acc, c| acc * 3 + c)
+}
+
+// Inverse of encode_score, for a 'len'-letter word.
+pub fn decode_score(score: BucketId, len: usize) -> Vec<CharScore> {
+    let mut cs = vec![CharScore::Absent; len];
+    let mut score = score;
+    for c in cs.iter_mut().rev() {
+        *c = match score % 3 {
+            0 => CharScore::Absent,
+            1 => CharScore::Correct,
+            _ => CharScore::Present,
+        };
+        score /= 3;
+    }
+    cs
+}
+
+// Render a score as Wordle's shareable emoji squares.
+pub fn render_score(score: BucketId, len: usize) -> String {
+    decode_score(score, len)
+        .iter()
+        .map(|c| match c {
+            CharScore::Absent => '⬛',
+            CharScore::Correct => '🟩',
+            CharScore::Present => '🟨',
+        })
+        .collect()
+}
+
+// Default for Scorer::log_batch.
+pub const DEFAULT_LOG_BATCH: usize = 50;
+
+// Longest word whose score fits in a u64: 3^40 < 2^64.
+pub const MAX_GENERIC_LEN: usize = 40;
+
+// Variations on the scoring rules, as used by some Wordle clones.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ScoringRules {
+    // The rules of Wordle itself.
+    Standard,
+    // The given byte is a blank tile in the answer, which scores as
+    // correct against any guessed letter.
+    WildcardAnswer(u8),
+    // Green letters don't use up their letter in the answer, so the
+    // same letter guessed elsewhere is also yellow. Each letter of the
+    // answer still makes at most one guessed letter yellow.
+    GreenImpliesYellowElsewhere,
+    // A letter is yellow wherever it's guessed if it's anywhere in the
+    // answer (and not green), however many times it's guessed.
+    UnlimitedYellows,
+}
+
+impl std::str::FromStr for ScoringRules {
+    type Err = WordleError;
+
+    // The names are as displayed, with the wildcard given after a
+    // colon, e.g. "wildcard:?".
+    fn from_str(s: &str) -> Result<ScoringRules, WordleError> {
+        match s {
+            "standard" => Ok(ScoringRules::Standard),
+            "greens-reusable" => Ok(ScoringRules::GreenImpliesYellowElsewhere),
+            "unlimited-yellows" => Ok(ScoringRules::UnlimitedYellows),
+            _ => match s.strip_prefix("wildcard:").map(str::as_bytes) {
+                Some([c]) => Ok(ScoringRules::WildcardAnswer(*c)),
+                _ => Err(WordleError::InvalidInput(format!(
+                    "Unknown rules '{}', expected 'standard', 'greens-reusable', \
+                     'unlimited-yellows' or 'wildcard:' and a character",
+                    s
+                ))),
+            },
+        }
+    }
+}
+
+impl fmt::Display for ScoringRules {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScoringRules::Standard => write!(f, "standard"),
+            ScoringRules::WildcardAnswer(c) => write!(f, "wildcard:{}", *c as char),
+            ScoringRules::GreenImpliesYellowElsewhere => write!(f, "greens-reusable"),
+            ScoringRules::UnlimitedYellows => write!(f, "unlimited-yellows"),
+        }
+    }
+}
+
+// How letters in the answer are used up in deciding what's yellow.
+#[derive(Clone, Copy)]
+enum Presence {
+    // By greens, and by each yellow.
+    Counted,
+    // Only by each yellow.
+    GreensReusable,
+    // Never.
+    Unlimited,
+}
+
+// The score for a 'len'-letter guess that is the answer.
+pub fn correct_score(len: usize) -> BucketId {
+    encode_score(std::iter::repeat_n(CharScore::Correct, len))
+}
+
+// Return the score for a guess against a specific actual answer, encoded.
+pub fn score_wordle(guess: &[u8], answer: &[u8]) -> BucketId {
+    score_with_rules(ScoringRules::Standard, guess, answer)
+}
+
+// As score_wordle, under the given rules.
+pub fn score_with_rules(rules: ScoringRules, guess: &[u8], answer: &[u8]) -> BucketId {
+    assert_eq!(guess.len(), answer.len());
+    assert!(guess.len() <= MAX_WORD_LEN);
+
+    let is_wildcard = |c: u8| matches!(rules, ScoringRules::WildcardAnswer(w) if w == c);
+    let presence = match rules {
+        ScoringRules::Standard | ScoringRules::WildcardAnswer(_) => Presence::Counted,
+        ScoringRules::GreenImpliesYellowElsewhere => Presence::GreensReusable,
+        ScoringRules::UnlimitedYellows => Presence::Unlimited,
+    };
+    // Wordle's own length, by far the common case, gets a copy of the
+    // scoring loop for that length (hence inlining score_generic_with),
+    // which builds the score cache much faster.
+    let score = if guess.len() == WORD_LEN {
+        score_generic_with(&guess[..WORD_LEN], &answer[..WORD_LEN], is_wildcard, presence)
+    } else {
+        score_generic_with(guess, answer, is_wildcard, presence)
+    };
+    score as BucketId
+}
+
+// The scoring algorithm for words over any alphabet (bytes, chars,
+// etc.) and of any length up to MAX_GENERIC_LEN, encoded as by
+// encode_score_wide.
+pub fn score_generic<T: Copy + Eq>(guess: &[T], answer: &[T]) -> u64 {
+    score_generic_with(guess, answer, |_| false, Presence::Counted)
+}
+
+// The scoring algorithm proper. 'is_wildcard' identifies blank tiles in
+// the answer, which match any letter.
+#[inline(always)]
+fn score_generic_with<T: Copy + Eq>(
+    guess: &[T],
+    answer: &[T],
+    is_wildcard: impl Fn(T) -> bool,
+    presence: Presence,
+) -> u64 {
+    assert_eq!(guess.len(), answer.len());
+    assert!(guess.len() <= MAX_GENERIC_LEN);
+
+    // Fixed-size arrays rather than Vecs, to avoid allocating in the
+    // score cache construction.
+    let mut corrects = [false; MAX_GENERIC_LEN];
+    let mut used = [false; MAX_GENERIC_LEN];
+    for idx in 0..guess.len() {
+        if guess[idx] == answer[idx] || is_wildcard(answer[idx]) {
+            corrects[idx] = true;
+            // Correctly guessed letters are "used up".
+            used[idx] = matches!(presence, Presence::Counted);
+        }
+    }
+
+    // Look for the presence of a character in the answer that isn't used,
+    // and if it's present use it up and return true. Otherwise false.
+    fn check_presence<T: Eq>(c: T, answer: &[T], used: &mut [bool], presence: Presence) -> bool {
+        for (idx, d) in answer.iter().enumerate() {
+            if !used[idx] && c == *d {
+                used[idx] = !matches!(presence, Presence::Unlimited);
+                return true;
+            }
+        }
+        false
+    }
+
+    encode_score_wide(corrects.iter().zip(guess.iter()).map(|(is_correct, c)| {
+        if *is_correct {
+            CharScore::Correct
+        } else if check_presence(*c, answer, &mut used, presence) {
+            CharScore::Present
+        } else {
+            CharScore::Absent
+        }
+    }))
+}
+
+////////////////////////////////////////////////////////////////////////
+// The Scorer holds the data and caches scoring information
+//
+
+// Errors from the library: loading word lists, parsing user input and
+// playing games.
+#[derive(Debug)]
+pub enum WordleError {
+    Io {
+        path: PathBuf,
+        error: std::io::Error,
+    },
+    InconsistentWordLength {
+        word: String,
+        source: String,
+        expected: usize,
+    },
+    // Words longer than MAX_WORD_LEN, or empty.
+    UnsupportedWordLength {
+        len: usize,
+        source: String,
+    },
+    UnknownSection {
+        section: String,
+        source: String,
+    },
+    BadGuessOrder {
+        source: String,
+    },
+    EmptyWordList {
+        source: String,
+    },
+    UnknownAnswer {
+        word: String,
+        source: String,
+    },
+    // A guess that isn't in the guess list.
+    UnknownGuess {
+        word: String,
+    },
+    // Malformed feedback, patterns, histories and so on, described by
+    // the message.
+    InvalidInput(String),
+    // Feedback for the guess that no remaining candidate could give.
+    NoCandidates {
+        guess: String,
+    },
+    // Feedback for the guess that the known answer wouldn't give.
+    InconsistentScore {
+        guess: String,
+        answer: String,
+    },
+    // Failing to get feedback from a game over HTTP.
+    Http {
+        url: String,
+        message: String,
+    },
+}
+
+// The old name, from when the only errors were in loading.
+pub type ScorerError = WordleError;
+
+impl fmt::Display for WordleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WordleError::Io { path, error } => {
+                write!(f, "Could not access '{}': {}", path.display(), error)
+            }
+            WordleError::InconsistentWordLength { word, source, expected } => write!(
+                f,
+                "Word '{}' in {} has length {}, expected {}",
+                word,
+                source,
+                word.len(),
+                expected
+            ),
+            WordleError::UnsupportedWordLength { len, source } => write!(
+                f,
+                "The words in {} are {} letters long, but only words of 1 to {} letters are supported",
+                source, len, MAX_WORD_LEN
+            ),
+            WordleError::UnknownSection { section, source } => write!(
+                f,
+                "Unknown section '[{}]' in {}, expected '[answers]' or '[guesses]'",
+                section, source
+            ),
+            WordleError::BadGuessOrder { source } => {
+                write!(f, "Guess order in {} does not match the guess list", source)
+            }
+            WordleError::EmptyWordList { source } => write!(f, "No words in the {}", source),
+            WordleError::UnknownAnswer { word, source } => {
+                write!(f, "Word '{}' in {} is not in the answer list", word, source)
+            }
+            WordleError::UnknownGuess { word } => write!(f, "'{}' is not in the word list", word),
+            WordleError::InvalidInput(message) => write!(f, "{}", message),
+            WordleError::NoCandidates { guess } => {
+                write!(f, "No candidate answers give that feedback for '{}'", guess)
+            }
+            WordleError::InconsistentScore { guess, answer } => {
+                write!(f, "'{}' would not give that feedback for '{}'", answer, guess)
+            }
+            WordleError::Http { url, message } => write!(f, "Request to '{}' failed: {}", url, message),
+        }
+    }
+}
+
+impl std::error::Error for WordleError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WordleError::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+// Read a file of newline-separated words.
+fn read_word_list(path: &Path) -> Result<Vec<String>, WordleError> {
+    let words = read_lines(path)?;
+    if let Some(first) = words.first() {
+        check_word_lengths(&words, &format!("'{}'", path.display()), first.len())?;
+    }
+    Ok(words)
+}
+
+// As read_word_list, without checking the word lengths.
+fn read_lines(path: &Path) -> Result<Vec<String>, WordleError> {
+    Ok(std::fs::read_to_string(path)
+        .map_err(|error| WordleError::Io {
+            path: path.to_path_buf(),
+            error,
+        })?
+        .lines()
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+        .collect::<Vec<String>>())
+}
+
+// Split a combined word list into guesses and answers. Either style
+// may be used, or both:
+//
+//  * Lines starting with '*' are answers, and other lines are guesses.
+//  * A line '[answers]' or '[guesses]' starts a section, and the words
+//    that follow are of that kind, until the next section. Words
+//    before any section are guesses.
+//
+// Blank lines are ignored.
+fn parse_combined(text: &str, source: &str) -> Result<(Vec<String>, Vec<String>), WordleError> {
+    let mut guesses = Vec::new();
+    let mut answers = Vec::new();
+    let mut in_answers = false;
+    for line in text.lines().map(str::trim).filter(|s| !s.is_empty()) {
+        if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            in_answers = match section {
+                "answers" => true,
+                "guesses" => false,
+                _ => {
+                    return Err(WordleError::UnknownSection {
+                        section: section.to_string(),
+                        source: source.to_string(),
+                    })
+                }
+            };
+        } else if let Some(answer) = line.strip_prefix('*') {
+            answers.push(answer.to_string());
+        } else if in_answers {
+            answers.push(line.to_string());
+        } else {
+            guesses.push(line.to_string());
+        }
+    }
+    // The words are all as long as the first.
+    if let Some(len) = answers.iter().chain(guesses.iter()).map(|w| w.len()).next() {
+        check_word_lengths(&answers, source, len)?;
+        check_word_lengths(&guesses, source, len)?;
+    }
+    Ok((guesses, answers))
+}
+
+// Scoring assumes all words are the same length, 'len', so catch bad
+// input before it gets that far.
+fn check_word_lengths(words: &[String], source: &str, len: usize) -> Result<(), WordleError> {
+    if len == 0 || len > MAX_WORD_LEN {
+        return Err(WordleError::UnsupportedWordLength {
+            len,
+            source: source.to_string(),
+        });
+    }
+    match words.iter().find(|w| w.len() != len) {
+        Some(word) => Err(WordleError::InconsistentWordLength {
+            word: word.clone(),
+            source: source.to_string(),
+            expected: len,
+        }),
+        None => Ok(()),
+    }
+}
+
+// Metrics by which guesses can be ranked.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Ranking {
+    // Size of the largest bucket.
+    WorstCase,
+    // Expected number of candidates left after the guess.
+    ExpectedRemaining,
+    // Number of distinct buckets, maximised, with ties broken by the
+    // size of the largest bucket.
+    MaximizePartitions,
+    // A look further ahead than WorstCase: the most candidates that can
+    // be left after the next guess as well, if that's the best of the
+    // candidates in its bucket. Ties are broken by the size of the
+    // largest bucket.
+    ResidualWorstCase,
+}
+
+// How a guess fares under a ranking: the metric itself, and then a
+// tie-breaker. Lower is better for both.
+pub type Rank = (f64, usize);
+
+impl std::str::FromStr for Ranking {
+    type Err = WordleError;
+
+    fn from_str(s: &str) -> Result<Ranking, WordleError> {
+        match s {
+            "worst-case" => Ok(Ranking::WorstCase),
+            "expected-remaining" => Ok(Ranking::ExpectedRemaining),
+            "maximize-partitions" => Ok(Ranking::MaximizePartitions),
+            "residual-worst-case" => Ok(Ranking::ResidualWorstCase),
+            _ => Err(WordleError::InvalidInput(format!(
+                "Unknown ranking '{}', expected 'worst-case', 'expected-remaining', 'maximize-partitions' \
+                 or 'residual-worst-case'",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Ranking {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Ranking::WorstCase => "worst-case",
+            Ranking::ExpectedRemaining => "expected-remaining",
+            Ranking::MaximizePartitions => "maximize-partitions",
+            Ranking::ResidualWorstCase => "residual-worst-case",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Options controlling how a Scorer is built.
+#[derive(Clone, Debug)]
+pub struct ScorerOptions {
+    // Append the answers to the guesses, as they're valid guesses too.
+    // If this is turned off, the guesses should already contain them.
+    pub append_answers: bool,
+    // Treat every guess as a possible answer, for clones that don't use
+    // a curated answer list. The score cache becomes guesses x guesses,
+    // about 340MB for the full dictionary rather than 60MB.
+    pub open_dictionary: bool,
+    // Drop guesses of the wrong length, rather than failing, to make
+    // use of a dictionary of words of all lengths.
+    pub filter_guess_length: bool,
+    // The rules to score guesses by.
+    pub rules: ScoringRules,
+    // If set, only cache the scores of this many of the best openers
+    // (by worst case), and of the answers, scoring other guesses on
+    // demand. This saves memory, but building the Scorer means scoring
+    // everything twice, and the search through all the guesses gets
+    // slower.
+    pub cached_guesses: Option<usize>,
+}
+
+impl Default for ScorerOptions {
+    fn default() -> ScorerOptions {
+        ScorerOptions {
+            append_answers: true,
+            open_dictionary: false,
+            filter_guess_length: false,
+            rules: ScoringRules::Standard,
+            cached_guesses: None,
+        }
+    }
+}
+
+// Directory holding the default word lists: the one named by
+// WORDLE_WORDS_DIR if set, "words" relative to the CWD otherwise.
+pub fn words_dir() -> PathBuf {
+    match std::env::var_os("WORDLE_WORDS_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from("words"),
+    }
+}
+
+// The rows of the score cache, one per guess, generated lazily. For the
+// full dictionary a Scorer holds all ~13k rows of 2315 two-byte
+// scores, about 60MB; a caller that only needs to stream over the scores (e.g. to
+// write them out, or to compute per-guess statistics) can use this
+// directly and hold a single row at a time.
+pub fn score_rows<'a>(
+    guesses: &'a [String],
+    answers: &'a [String],
+) -> impl Iterator<Item = Vec<BucketId>> + 'a {
+    score_rows_with_rules(ScoringRules::Standard, guesses, answers)
+}
+
+// As score_rows, under the given rules.
+pub fn score_rows_with_rules<'a>(
+    rules: ScoringRules,
+    guesses: &'a [String],
+    answers: &'a [String],
+) -> impl Iterator<Item = Vec<BucketId>> + 'a {
+    guesses.iter().map(move |g| {
+        let gbs = g.as_bytes();
+        answers
+            .iter()
+            .map(|a| score_with_rules(rules, gbs, a.as_bytes()))
+            .collect::<Vec<BucketId>>()
+    })
+}
+
+// As score_rows_with_rules, but only scoring against the answers with
+// the given indices, so that row entry 'i' is the score against
+// answers[columns[i]], for a caller that only cares about some of the
+// answers, e.g. the candidates left in a game. (The Scorer itself
+// doesn't need this: guesses missing from the score cache are scored
+// against just the answers asked about, one at a time.)
+pub fn score_columns<'a>(
+    rules: ScoringRules,
+    guesses: &'a [String],
+    answers: &'a [String],
+    columns: &'a [usize],
+) -> impl Iterator<Item = Vec<BucketId>> + 'a {
+    guesses.iter().map(move |g| {
+        let gbs = g.as_bytes();
+        columns
+            .iter()
+            .map(|a| score_with_rules(rules, gbs, answers[*a].as_bytes()))
+            .collect::<Vec<BucketId>>()
+    })
+}
+
+// How the guesses and answers given overlap, and how many distinct
+// guesses that leaves once the answers are added.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct WordListStats {
+    pub guesses: usize,
+    pub answers: usize,
+    // Distinct answers that were also in the guesses given.
+    pub overlap: usize,
+    pub distinct_guesses: usize,
+}
+
+impl fmt::Display for WordListStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} guesses and {} answers, {} in both: {} distinct guesses",
+            self.guesses, self.answers, self.overlap, self.distinct_guesses
+        )
+    }
+}
+
+// Which guesses to cache for ScorerOptions::cached_guesses: the best
+// 'num_cached' by worst case, and any that are answers.
+fn cached_guesses(
+    guesses: &[String],
+    answers: &[String],
+    rules: ScoringRules,
+    num_cached: usize,
+    word_len: usize,
+) -> Vec<bool> {
+    let mut counts = vec![0usize; num_buckets(word_len)];
+    let mut ranked = score_rows_with_rules(rules, guesses, answers)
+        .enumerate()
+        .map(|(idx, row)| {
+            counts.fill(0);
+            for score in row.iter() {
+                counts[*score as usize] += 1;
+            }
+            (counts.iter().copied().max().unwrap_or(0), idx)
+        })
+        .collect::<Vec<_>>();
+    ranked.sort_unstable();
+
+    let answer_set = answers.iter().map(|a| a.as_str()).collect::<HashSet<_>>();
+    let mut cached = guesses.iter().map(|g| answer_set.contains(g.as_str())).collect::<Vec<_>>();
+    for (_, idx) in ranked.iter().take(num_cached) {
+        cached[*idx] = true;
+    }
+    cached
+}
+
+// The body of Scorer::for_each_bucket, given each answer's score, for
+// scores below 'num_buckets'. Words up to Wordle's length, by far the
+// common case, count on the stack.
+fn bucket_by(
+    num_buckets: usize,
+    answers: &[usize],
+    score: impl Fn(usize) -> BucketId,
+    f: impl FnMut(BucketId, &[usize]),
+) {
+    const STACK_BUCKETS: usize = crate::num_buckets(WORD_LEN);
+    if num_buckets <= STACK_BUCKETS {
+        bucket_into([0; STACK_BUCKETS], answers, score, f)
+    } else {
+        bucket_into(vec![0; num_buckets], answers, score, f)
+    }
+}
+
+// bucket_by, counting into 'counts', zeroed, with room for every score.
+fn bucket_into<C: AsRef<[usize]> + AsMut<[usize]> + Clone>(
+    mut counts: C,
+    answers: &[usize],
+    score: impl Fn(usize) -> BucketId,
+    mut f: impl FnMut(BucketId, &[usize]),
+) {
+    for answer in answers.iter() {
+        counts.as_mut()[score(*answer) as usize] += 1;
+    }
+
+    let mut starts = counts.clone();
+    let mut total = 0;
+    for (start, count) in starts.as_mut().iter_mut().zip(counts.as_ref().iter()) {
+        *start = total;
+        total += count;
+    }
+
+    let mut sorted = vec![0; answers.len()];
+    let mut next = starts.clone();
+    for answer in answers.iter() {
+        let score = score(*answer) as usize;
+        sorted[next.as_ref()[score]] = *answer;
+        next.as_mut()[score] += 1;
+    }
+
+    for (score, (start, count)) in starts.as_ref().iter().zip(counts.as_ref().iter()).enumerate() {
+        if *count != 0 {
+            f(score as BucketId, &sorted[*start..*start + *count]);
+        }
+    }
+}
+
+// Map each word to its first index in the list.
+fn index_words(words: &[String]) -> HashMap<String, usize> {
+    let mut indices = HashMap::with_capacity(words.len());
+    for (idx, word) in words.iter().enumerate() {
+        indices.entry(word.clone()).or_insert(idx);
+    }
+    indices
+}
+
+// As Scorer::rank_guess, for a guess with the given scores against the
+// answers, out of 'num_scores' possible.
+fn rank_by(ranking: Ranking, num_scores: usize, answers: &[usize], score: impl Fn(usize) -> BucketId) -> Rank {
+    let mut worst_case = 0;
+    let mut num_buckets = 0;
+    let mut sum_of_squares = 0;
+    bucket_by(num_scores, answers, score, |_, members| {
+        worst_case = worst_case.max(members.len());
+        num_buckets += 1;
+        sum_of_squares += members.len() * members.len();
+    });
+    match ranking {
+        Ranking::WorstCase => (worst_case as f64, 0),
+        Ranking::ExpectedRemaining if answers.is_empty() => (0.0, 0),
+        Ranking::ExpectedRemaining => (sum_of_squares as f64 / answers.len() as f64, 0),
+        Ranking::MaximizePartitions => (-(num_buckets as f64), worst_case),
+        // This needs the scores between the answers in each bucket, so
+        // best_answer_guess uses rank_guess instead.
+        Ranking::ResidualWorstCase => unreachable!("rank_by can't rank by residual worst case"),
+    }
+}
+
+pub struct Scorer {
+    // Once the scores are precalculated, we refer to everything by indices.
+    pub guesses: Vec<String>,
+    pub answers: Vec<String>,
+    // Scores of each guess against each answer. Guesses that aren't
+    // cached (see ScorerOptions::cached_guesses) have an empty row, and
+    // are scored on demand, so use score() rather than reading this
+    // directly.
+    pub score_cache: Vec<Vec<BucketId>>,
+
+    // The length of every guess and answer.
+    pub word_len: usize,
+
+    // With this many candidates or fewer, best_guess picks the guess
+    // that minimises the expected number of guesses exactly, rather
+    // than by ranking. 0 turns this off.
+    pub endgame_limit: usize,
+    // With this many candidates or fewer, only guess answers, scored
+    // from answer_matrix, the scores of each answer against each answer.
+    // That's a sixth the size of the full score cache, so stays in cache
+    // better. See use_answer_endgame.
+    answer_endgame_limit: usize,
+    answer_matrix: Vec<Vec<BucketId>>,
+
+    // Break ties between equally good guesses by the order the guesses
+    // were given in, e.g. for a guess list in order of how common the
+    // words are, rather than by the current guess order, which the
+    // search reorders.
+    pub prefer_input_order: bool,
+    // Only make guesses allowed in hard mode when solving greedily or
+    // suggesting guesses for a game. See is_hard_mode_legal.
+    pub hard_mode: bool,
+    // Where each guess was in the order given.
+    input_order: Vec<usize>,
+
+    // Keep the rows score_row_vs_guesses works out, for reuse. They're
+    // only kept for the guesses asked about, so this costs far less
+    // than a full guesses x guesses cache would.
+    pub cache_guess_rows: bool,
+    guess_rows: Mutex<HashMap<usize, Vec<BucketId>>>,
+
+    // Progress logged while solving every answer goes to stderr in
+    // batches, flushed at most once per this many answers, as locking
+    // and flushing stderr for every line slows a long run down in a
+    // terminal. 1 writes each line straight away.
+    pub log_batch: usize,
+
+    // The rules the score cache was built with.
+    pub rules: ScoringRules,
+
+    // How the word lists were combined.
+    pub word_list_stats: WordListStats,
+
+    // The letters the words use, for sizing per-letter tables.
+    pub alphabet: Alphabet,
+
+    // Index of each word, for looking up words typed in by the user.
+    // A word in either list more than once maps to its first index.
+    guess_indices: HashMap<String, usize>,
+    answer_indices: HashMap<String, usize>,
+
+    // Awkward place to put reused vector.
+    bucket_vec: Vec<Vec<usize>>,
+}
+
+impl Scorer {
+    // Load the word lists from their default locations: the directory
+    // named by WORDLE_WORDS_DIR if set, "words" relative to the CWD
+    // otherwise.
+    pub fn new() -> Result<Scorer, WordleError> {
+        Scorer::with_options(&ScorerOptions::default())
+    }
+
+    // As new, with the given options.
+    pub fn with_options(options: &ScorerOptions) -> Result<Scorer, WordleError> {
+        let dir = words_dir();
+        Scorer::from_paths_with_options(
+            dir.join("possible_guesses.txt"),
+            dir.join("possible_solutions.txt"),
+            options,
+        )
+    }
+
+    pub fn from_paths(
+        guesses_path: impl AsRef<Path>,
+        answers_path: impl AsRef<Path>,
+    ) -> Result<Scorer, WordleError> {
+        Scorer::from_paths_with_options(guesses_path, answers_path, &ScorerOptions::default())
+    }
+
+    pub fn from_paths_with_options(
+        guesses_path: impl AsRef<Path>,
+        answers_path: impl AsRef<Path>,
+        options: &ScorerOptions,
+    ) -> Result<Scorer, WordleError> {
+        // Load the strings...
+        let guesses = if options.filter_guess_length {
+            read_lines(guesses_path.as_ref())?
+        } else {
+            read_word_list(guesses_path.as_ref())?
+        };
+        Scorer::from_word_lists_with_options(guesses, read_word_list(answers_path.as_ref())?, options)
+    }
+
+    // Load from a single file containing both guesses and answers, in
+    // the format described by parse_combined.
+    pub fn from_combined(path: impl AsRef<Path>) -> Result<Scorer, WordleError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|error| WordleError::Io {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        let (guesses, answers) = parse_combined(&text, &format!("'{}'", path.display()))?;
+        Scorer::from_word_lists(guesses, answers)
+    }
+
+    // Build a Scorer from word lists already in memory.
+    pub fn from_word_lists(guesses: Vec<String>, answers: Vec<String>) -> Result<Scorer, WordleError> {
+        Scorer::from_word_lists_with_options(guesses, answers, &ScorerOptions::default())
+    }
+
+    pub fn from_word_lists_with_options(
+        mut guesses: Vec<String>,
+        mut answers: Vec<String>,
+        options: &ScorerOptions,
+    ) -> Result<Scorer, WordleError> {
+        // Everything else assumes there's something to guess. Missing
+        // answers get added to the guesses, so that covers them too.
+        if answers.is_empty() {
+            return Err(WordleError::EmptyWordList {
+                source: String::from("answers"),
+            });
+        }
+        // The answers set the word length.
+        let word_len = answers[0].len();
+        if options.filter_guess_length {
+            let num_guesses = guesses.len();
+            guesses.retain(|g| g.len() == word_len);
+            if guesses.len() < num_guesses {
+                eprintln!(
+                    "Dropped {} guesses that aren't {} letters long",
+                    num_guesses - guesses.len(),
+                    word_len
+                );
+            }
+        }
+        check_word_lengths(&answers, "answers", word_len)?;
+        check_word_lengths(&guesses, "guesses", word_len)?;
+
+        let given_guesses = guesses.iter().map(|g| g.as_str()).collect::<HashSet<_>>();
+        let overlap = answers
+            .iter()
+            .map(|a| a.as_str())
+            .collect::<HashSet<_>>()
+            .intersection(&given_guesses)
+            .count();
+        let num_given_guesses = guesses.len();
+
+        // Answers are also possible guesses!
+        if options.append_answers {
+            for answer in answers.iter() {
+                guesses.push(answer.clone());
+            }
+        }
+
+        // The solvers rely on being able to guess the answer once it's
+        // been identified, so any answer missing from the guesses has
+        // to be added.
+        let known_guesses = guesses.iter().cloned().collect::<HashSet<String>>();
+        let missing = answers
+            .iter()
+            .filter(|a| !known_guesses.contains(*a))
+            .cloned()
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            eprintln!(
+                "Warning: adding {} answers missing from the guess list (e.g. '{}')",
+                missing.len(),
+                missing[0]
+            );
+            guesses.extend(missing);
+        }
+
+        if options.open_dictionary {
+            let mut seen = HashSet::new();
+            answers = guesses.iter().filter(|g| seen.insert(*g)).cloned().collect();
+        }
+
+        // Score them all up-front.
+        let rows = score_rows_with_rules(options.rules, &guesses, &answers);
+        let score_cache = match options.cached_guesses {
+            Some(num_cached) => {
+                let cached = cached_guesses(&guesses, &answers, options.rules, num_cached, word_len);
+                rows.zip(cached.iter())
+                    .map(|(row, cached)| if *cached { row } else { Vec::new() })
+                    .collect::<Vec<_>>()
+            }
+            None => rows.collect::<Vec<_>>(),
+        };
+
+        let alphabet = Alphabet::from_words(guesses.iter().map(|g| g.as_str()));
+        let guess_indices = index_words(&guesses);
+        let word_list_stats = WordListStats {
+            guesses: num_given_guesses,
+            answers: answers.len(),
+            overlap,
+            distinct_guesses: guess_indices.len(),
+        };
+        let answer_indices = index_words(&answers);
+        let bucket_vec = (0..num_buckets(word_len)).map(|_| Vec::new()).collect::<Vec<_>>();
+
+        let input_order = (0..guesses.len()).collect();
+        let scorer = Scorer {
+            guesses,
+            answers,
+            score_cache,
+            word_len,
+            endgame_limit: 0,
+            answer_endgame_limit: 0,
+            answer_matrix: Vec::new(),
+            prefer_input_order: false,
+            hard_mode: false,
+            input_order,
+            cache_guess_rows: false,
+            guess_rows: Mutex::new(HashMap::new()),
+            log_batch: DEFAULT_LOG_BATCH,
+            rules: options.rules,
+            word_list_stats,
+            alphabet,
+            guess_indices,
+            answer_indices,
+            bucket_vec
+        };
+        #[cfg(debug_assertions)]
+        scorer.check_score_cache();
+        Ok(scorer)
+    }
+
+    // The score of a guess against an answer, from the cache if it's
+    // there.
+    pub fn score(&self, guess: usize, answer: usize) -> BucketId {
+        match self.score_cache[guess].get(answer) {
+            Some(score) => *score,
+            None => score_with_rules(self.rules, self.guesses[guess].as_bytes(), self.answers[answer].as_bytes()),
+        }
+    }
+
+    // The number of distinct scores for the words.
+    pub fn num_buckets(&self) -> usize {
+        num_buckets(self.word_len)
+    }
+
+    // The score for a guess that is the answer.
+    pub fn correct_score(&self) -> BucketId {
+        correct_score(self.word_len)
+    }
+
+    // The scores of a guess against every answer.
+    pub fn scores(&self, guess: usize) -> Cow<'_, [BucketId]> {
+        let row = &self.score_cache[guess];
+        if row.is_empty() {
+            Cow::Owned(score_rows_with_rules(self.rules, &self.guesses[guess..=guess], &self.answers).next().unwrap())
+        } else {
+            Cow::Borrowed(row)
+        }
+    }
+
+    // The scores of a guess against every guess, rather than every
+    // answer, e.g. for treating guesses as candidates. Worked out on
+    // demand, and kept if cache_guess_rows is set.
+    pub fn score_row_vs_guesses(&self, guess: usize) -> Vec<BucketId> {
+        if let Some(row) = self.guess_rows.lock().unwrap().get(&guess) {
+            return row.clone();
+        }
+        let row = score_rows_with_rules(self.rules, &self.guesses[guess..=guess], &self.guesses).next().unwrap();
+        if self.cache_guess_rows {
+            self.guess_rows.lock().unwrap().insert(guess, row.clone());
+        }
+        row
+    }
+
+    // Spot-check the score cache against scoring from scratch, to catch
+    // bugs in building it.
+    #[cfg(debug_assertions)]
+    fn check_score_cache(&self) {
+        const NUM_CHECKS: usize = 1000;
+        let mut rng = Rng::new(0);
+        for _ in 0..NUM_CHECKS {
+            let guess = rng.below(self.guesses.len());
+            let answer = rng.below(self.answers.len());
+            if self.score_cache[guess].is_empty() {
+                continue;
+            }
+            assert_eq!(
+                self.score_cache[guess][answer],
+                score_with_rules(self.rules, self.guesses[guess].as_bytes(), self.answers[answer].as_bytes()),
+                "Score cache is wrong for guess '{}' against '{}'",
+                self.guesses[guess],
+                self.answers[answer]
+            );
+        }
+    }
+
+    // Given a guess, bucket the answer list entries by the score they return.
+    //
+    // What we'd like to do is have each bucket contain a single entry,
+    // indicating that the guess has uniquely identified all possibile
+    // answers. A bucket with more than one entry will require further
+    // guessing to identify a unique answer.
+    pub fn bucket_answers(&self, guess: usize, answers: &[usize]) -> Vec<Vec<usize>> {
+        let mut buckets = HashMap::new();
+
+        for answer in answers.iter() {
+            let score = self.score(guess, *answer);
+            buckets
+                .entry(score)
+                .or_insert_with(Vec::new)
+                .push(*answer);
+        }
+
+        let mut v: Vec<_> = buckets.into_values().map(|v| (v.len(), v)).collect();
+        v.sort_by(|a, b| b.cmp(a));
+        v.into_iter().map(|(_k, v)| v).collect()
+    }
+
+    // Allocation-light alternative to bucket_answers for callers that
+    // only want to inspect the buckets. Calls 'f' with the score and
+    // members of each non-empty bucket, in score order. The answers are
+    // counting-sorted into a single buffer, rather than building a
+    // vector per bucket.
+    pub fn for_each_bucket(
+        &self,
+        guess: usize,
+        answers: &[usize],
+        f: impl FnMut(BucketId, &[usize]),
+    ) {
+        // Keep the common, cached case fast.
+        let row = &self.score_cache[guess];
+        if row.is_empty() {
+            bucket_by(self.num_buckets(), answers, |answer| self.score(guess, answer), f)
+        } else {
+            bucket_by(self.num_buckets(), answers, |answer| row[answer], f)
+        }
+    }
+
+    // Given a guess, find the size of the largest bucket, which is a
+    // heuristic for the hardest case to solve.
+    pub fn find_greedy_worst_case(&self, guess: usize, answers: &[usize]) -> usize {
+        let mut worst_case = 0;
+        self.for_each_bucket(guess, answers, |_, members| {
+            worst_case = worst_case.max(members.len());
+        });
+        worst_case
+    }
+
+    // For each of the guess's buckets, the fewest candidates that can be
+    // left in the worst case after guessing one of the bucket's answers
+    // next (and 0 if there's only one), returning the largest: roughly
+    // how hard the guess's hardest bucket is to finish off. Only the
+    // bucket's own answers are tried as the next guess, to keep this
+    // affordable for openers.
+    pub fn residual_worst_case(&self, guess: usize, answers: &[usize]) -> usize {
+        let mut residual = 0;
+        let mut counts = vec![0usize; self.num_buckets()];
+        self.for_each_bucket(guess, answers, |_, members| {
+            // Guessing one of them leaves at most the others.
+            if members.len() <= residual + 1 {
+                return;
+            }
+            let best = members
+                .iter()
+                .map(|next| {
+                    let row = self.scores(self.guess_index(&self.answers[*next]).unwrap());
+                    let mut worst_case = 0;
+                    for other in members.iter().filter(|other| *other != next) {
+                        let count = &mut counts[row[*other] as usize];
+                        *count += 1;
+                        worst_case = worst_case.max(*count);
+                    }
+                    for other in members.iter() {
+                        counts[row[*other] as usize] = 0;
+                    }
+                    worst_case
+                })
+                .min()
+                .unwrap();
+            residual = residual.max(best);
+        });
+        residual
+    }
+
+    // Version of bucket_answers used for the 3-guess case.
+    fn bucket_answers3(&mut self, guess: usize, answers: &[usize]) {
+        for bucket in self.bucket_vec.iter_mut() {
+            bucket.clear();
+        }
+
+        for answer in answers.iter() {
+            let score = self.score(guess, *answer);
+            self.bucket_vec[score as usize].push(*answer);
+        }
+
+        self.bucket_vec.sort_by_key(|b| std::cmp::Reverse(b.len()));
+    }
+
+    // The expected number of candidates remaining after making a guess,
+    // assuming each answer is equally likely: each bucket is hit with
+    // probability (size / total), and leaves 'size' candidates.
+    pub fn expected_remaining(&self, guess: usize, answers: &[usize]) -> f64 {
+        if answers.is_empty() {
+            return 0.0;
+        }
+        let mut sum_of_squares = 0;
+        self.for_each_bucket(guess, answers, |_, members| {
+            sum_of_squares += members.len() * members.len();
+        });
+        sum_of_squares as f64 / answers.len() as f64
+    }
+
+    // The number of answers a guess leaves alone in their bucket, which
+    // can then be guessed next time. The bucket for the guess being the
+    // answer doesn't count, as that's already solved.
+    pub fn singleton_count(&self, guess: usize, answers: &[usize]) -> usize {
+        let mut count = 0;
+        self.for_each_bucket(guess, answers, |_, members| {
+            if members.len() == 1 && self.answers[members[0]] != self.guesses[guess] {
+                count += 1;
+            }
+        });
+        count
+    }
+
+    // The expected information gained from a guess, in bits: the
+    // entropy of the distribution of scores over the answers.
+    pub fn entropy(&self, guess: usize, answers: &[usize]) -> f64 {
+        let total = answers.len() as f64;
+        let mut entropy = 0.0;
+        self.for_each_bucket(guess, answers, |_, members| {
+            let p = members.len() as f64 / total;
+            entropy -= p * p.log2();
+        });
+        entropy
+    }
+
+    // Find the guess that maximises the expected information gained,
+    // returning it along with the entropy.
+    pub fn find_max_entropy(&self, answers: &[usize]) -> Option<(usize, f64)> {
+        (0..self.guesses.len())
+            .map(|guess| (guess, self.entropy(guess, answers)))
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap().then(b.0.cmp(&a.0)))
+    }
+
+    // The entropy of every guess against the answers, indexed by guess,
+    // worked out in parallel.
+    pub fn entropies(&self, answers: &[usize]) -> Vec<f64> {
+        (0..self.guesses.len())
+            .into_par_iter()
+            .map(|guess| self.entropy(guess, answers))
+            .collect()
+    }
+
+    // Split the answers into the groups that can't be told apart after
+    // making all the given guesses, regardless of their feedback.
+    pub fn partition_by_guesses(&self, guesses: &[usize], answers: &[usize]) -> Vec<Vec<usize>> {
+        guesses.iter().fold(vec![answers.to_vec()], |partition, guess| {
+            partition
+                .iter()
+                .flat_map(|part| self.bucket_answers(*guess, part))
+                .collect()
+        })
+    }
+
+    // A small set of guesses that, played together, tell every answer
+    // apart, if possible. Finding the smallest is hard, so this is
+    // greedy: keep adding whichever guess separates the most pairs of
+    // answers not yet told apart, until none separates any more. See
+    // partition_by_guesses for what's left ambiguous.
+    pub fn separating_set_greedy(&self) -> Vec<usize> {
+        let mut classes = vec![(0..self.answers.len()).collect::<Vec<_>>()];
+        let mut chosen = Vec::new();
+        loop {
+            classes.retain(|class| class.len() > 1);
+            if classes.is_empty() {
+                return chosen;
+            }
+
+            // Pairs left together are counted by the sum of the squared
+            // sizes of the classes, so minimise that. Counts are kept per
+            // (class, score), a bucket within a class.
+            let current = classes.iter().map(|class| class.len() * class.len()).sum::<usize>();
+            let num_buckets = self.num_buckets();
+            let mut counts = vec![0u32; classes.len() * num_buckets];
+            let (best, guess) = (0..self.guesses.len())
+                .map(|guess| {
+                    let row = self.scores(guess);
+                    let mut sum_of_squares = 0;
+                    for (idx, class) in classes.iter().enumerate() {
+                        for answer in class.iter() {
+                            let count = &mut counts[idx * num_buckets + row[*answer] as usize];
+                            sum_of_squares += 2 * *count as usize + 1;
+                            *count += 1;
+                        }
+                    }
+                    for (idx, class) in classes.iter().enumerate() {
+                        for answer in class.iter() {
+                            counts[idx * num_buckets + row[*answer] as usize] = 0;
+                        }
+                    }
+                    (sum_of_squares, guess)
+                })
+                .min()
+                .unwrap();
+            if best >= current {
+                return chosen;
+            }
+            chosen.push(guess);
+            classes = classes.iter().flat_map(|class| self.bucket_answers(guess, class)).collect();
+        }
+    }
+
+    // Groups of answers that are anagrams of each other, and so can only
+    // be told apart by where their letters are. Groups are in order of
+    // their first answer, and only those of more than one are returned.
+    pub fn anagram_clusters(&self) -> Vec<Vec<usize>> {
+        let mut clusters: HashMap<Vec<u8>, Vec<usize>> = HashMap::new();
+        for (idx, answer) in self.answers.iter().enumerate() {
+            let mut key = answer.as_bytes().to_vec();
+            key.sort_unstable();
+            clusters.entry(key).or_default().push(idx);
+        }
+        let mut clusters = clusters.into_values().filter(|c| c.len() > 1).collect::<Vec<_>>();
+        clusters.sort_unstable();
+        clusters
+    }
+
+    // Pairs of answers that no other answer tells apart as a guess, so
+    // that, guessing only answers, the one way to separate them is to
+    // guess one and risk being wrong. (Guessing either always tells
+    // them apart, so those guesses don't count.) If there are none,
+    // restricting guesses to the answers costs little. Pairs are of
+    // answer indices, in order.
+    pub fn answer_only_indistinguishable_pairs(&self) -> Vec<(usize, usize)> {
+        let rows = (0..self.answers.len())
+            .map(|answer| self.scores(self.guess_index(&self.answers[answer]).unwrap()))
+            .collect::<Vec<_>>();
+        let mut pairs = Vec::new();
+        for a in 0..self.answers.len() {
+            for b in a + 1..self.answers.len() {
+                // Nearly all pairs are told apart by the first few
+                // guesses tried.
+                let separated = rows
+                    .iter()
+                    .enumerate()
+                    .any(|(guess, row)| guess != a && guess != b && row[a] != row[b]);
+                if !separated {
+                    pairs.push((a, b));
+                }
+            }
+        }
+        pairs
+    }
+
+    // The guesses made of distinct letters, all among the 'num_letters'
+    // letters found in the most answers: openers that are easy to
+    // remember, if not the best.
+    pub fn common_letter_guesses(&self, answers: &[usize], num_letters: usize) -> Vec<usize> {
+        let alphabet = &self.alphabet;
+        let mut frequency = vec![0; alphabet.len()];
+        for answer in answers.iter() {
+            let mut seen = vec![false; alphabet.len()];
+            for c in self.answers[*answer].bytes() {
+                seen[alphabet.index(c)] = true;
+            }
+            for (count, seen) in frequency.iter_mut().zip(seen.iter()) {
+                *count += *seen as usize;
+            }
+        }
+        let mut letters = (0..alphabet.len()).collect::<Vec<_>>();
+        letters.sort_by_key(|letter| std::cmp::Reverse(frequency[*letter]));
+        let mut common = vec![false; alphabet.len()];
+        for letter in letters.iter().take(num_letters) {
+            common[*letter] = true;
+        }
+
+        (0..self.guesses.len())
+            .filter(|guess| {
+                let mut used = vec![false; alphabet.len()];
+                self.guesses[*guess].bytes().all(|c| {
+                    let letter = alphabet.index(c);
+                    common[letter] && !std::mem::replace(&mut used[letter], true)
+                })
+            })
+            .collect()
+    }
+
+    // The best pair of openers to play together, whatever the first
+    // feedback, by expected candidates remaining after both. Trying
+    // every pair is too slow, so the second word comes from the
+    // shortlist_k best single openers. Returns both words and the
+    // expected remaining, or None if there's no pair to try: fewer than
+    // two guesses, or an empty shortlist.
+    pub fn best_opener_pair(&self, answers: &[usize], shortlist_k: usize) -> Option<(String, String, f64)> {
+        let mut ranked = (0..self.guesses.len())
+            .map(|guess| (self.expected_remaining(guess, answers), guess))
+            .collect::<Vec<_>>();
+        ranked.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let shortlist = ranked.iter().take(shortlist_k).map(|(_, guess)| *guess).collect::<Vec<_>>();
+
+        // Sum of squared bucket sizes, over buckets for each pair of scores.
+        // Counting those densely takes the square of the number of scores,
+        // too many for long words, which count in a map instead.
+        let num_buckets = self.num_buckets();
+        let dense_size = if num_buckets <= MAX_DENSE_BUCKETS { num_buckets * num_buckets } else { 0 };
+        let mut counts = vec![0usize; dense_size];
+        let mut sparse_counts = HashMap::new();
+        let mut best: Option<(usize, usize, usize)> = None;
+        for first in 0..self.guesses.len() {
+            for second in shortlist.iter().copied().filter(|second| *second != first) {
+                let key = |answer: usize| {
+                    self.score(first, answer) as usize * num_buckets
+                        + self.score(second, answer) as usize
+                };
+                let mut sum_of_squares = 0;
+                for answer in answers.iter() {
+                    let count = match counts.get_mut(key(*answer)) {
+                        Some(count) => count,
+                        None => sparse_counts.entry(key(*answer)).or_insert(0),
+                    };
+                    sum_of_squares += 2 * *count + 1;
+                    *count += 1;
+                }
+                for answer in answers.iter() {
+                    if let Some(count) = counts.get_mut(key(*answer)) {
+                        *count = 0;
+                    }
+                }
+                sparse_counts.clear();
+                if best.is_none_or(|(best_sum, _, _)| sum_of_squares < best_sum) {
+                    best = Some((sum_of_squares, first, second));
+                }
+            }
+        }
+
+        let (sum_of_squares, first, second) = best?;
+        Some((
+            self.guesses[first].clone(),
+            self.guesses[second].clone(),
+            sum_of_squares as f64 / answers.len() as f64,
+        ))
+    }
+
+    // The number of distinct scores a guess produces over the answers.
+    pub fn bucket_count(&self, guess: usize, answers: &[usize]) -> usize {
+        let mut count = 0;
+        self.for_each_bucket(guess, answers, |_, _| count += 1);
+        count
+    }
+
+    // A one-line justification for a guess, e.g. "splits 128
+    // candidates into 47 groups; worst case 6 remain".
+    pub fn explain_guess(&self, guess: usize, answers: &[usize]) -> String {
+        let mut groups = 0;
+        let mut worst_case = 0;
+        self.for_each_bucket(guess, answers, |_, members| {
+            groups += 1;
+            worst_case = worst_case.max(members.len());
+        });
+        let outcome = if worst_case <= 1 {
+            String::from("the answer is then known")
+        } else {
+            format!("worst case {} remain", worst_case)
+        };
+        format!("splits {} candidates into {} groups; {}", answers.len(), groups, outcome)
+    }
+
+    // Score a guess under the given ranking. Lower is better.
+    pub fn rank_guess(&self, ranking: Ranking, guess: usize, answers: &[usize]) -> Rank {
+        match ranking {
+            Ranking::WorstCase => (self.find_greedy_worst_case(guess, answers) as f64, 0),
+            Ranking::ExpectedRemaining => (self.expected_remaining(guess, answers), 0),
+            Ranking::MaximizePartitions => (
+                -(self.bucket_count(guess, answers) as f64),
+                self.find_greedy_worst_case(guess, answers),
+            ),
+            Ranking::ResidualWorstCase => (
+                self.residual_worst_case(guess, answers) as f64,
+                self.find_greedy_worst_case(guess, answers),
+            ),
+        }
+    }
+
+    // Optimise the order in which guesses are made, so that the best
+    // guesses under the given ranking (by default, those that minimise
+    // the largest bucket) come first. Returns a line per guess, in the
+    // new order, giving its rank, for the caller to log if it likes.
+    pub fn optimise_guess_order(&mut self, ranking: Ranking) -> Vec<String> {
+        let answer_nums = self
+            .answers
+            .iter()
+            .enumerate()
+            .map(|(idx, _)| idx)
+            .collect::<Vec<usize>>();
+        self.optimise_guess_order_for(ranking, &answer_nums)
+    }
+
+    // As optimise_guess_order, but ranking the guesses only by how they
+    // split the given answers, such as those of particular interest.
+    // The other answers are still possible.
+    pub fn optimise_guess_order_for(&mut self, ranking: Ranking, answers: &[usize]) -> Vec<String> {
+        let ranks = (0..self.guesses.len())
+            .map(|guess| self.rank_guess(ranking, guess, answers))
+            .collect::<Vec<_>>();
+        self.apply_guess_ranks(ranking, &ranks).1
+    }
+
+    // Sort the guesses by their ranks, best first. Returns the new
+    // order, as indices into the old one, and the log lines described
+    // in optimise_guess_order.
+    fn apply_guess_ranks(&mut self, ranking: Ranking, ranks: &[Rank]) -> (Vec<usize>, Vec<String>) {
+        let mut worst_cases: Vec<(Rank, usize, String)> = self.guesses
+            .iter()
+            .enumerate()
+            .map(|(idx, guess)| (ranks[idx], idx, guess.clone()))
+            .collect();
+        worst_cases.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap().then(a.1.cmp(&b.1)));
+
+        let log = worst_cases
+            .iter()
+            .map(|(worst_case, _idx, guess)| match ranking {
+                Ranking::WorstCase => format!("{}: {}", worst_case.0, guess),
+                Ranking::ExpectedRemaining => format!("{:.2}: {}", worst_case.0, guess),
+                Ranking::MaximizePartitions => format!("{} (worst case {}): {}", -worst_case.0, worst_case.1, guess),
+                Ranking::ResidualWorstCase => format!("{} (worst case {}): {}", worst_case.0, worst_case.1, guess),
+            })
+            .collect::<Vec<_>>();
+
+        // Sort the guess list and the score cache to match the
+        // improved search order.
+        let order = worst_cases.iter().map(|(_, idx, _)| *idx).collect::<Vec<_>>();
+        self.reorder_guesses(&order);
+        (order, log)
+    }
+
+    // Put the guesses (and their score cache rows) in the given order,
+    // a permutation of the guess indices. Guesses left out of the order
+    // are dropped.
+    fn reorder_guesses(&mut self, order: &[usize]) {
+        self.guesses = order.iter().map(|idx| self.guesses[*idx].clone()).collect();
+        self.guess_indices = index_words(&self.guesses);
+        self.input_order = order.iter().map(|idx| self.input_order[*idx]).collect();
+        self.guess_rows.lock().unwrap().clear();
+        self.score_cache = order.iter().map(|idx| self.score_cache[*idx].clone()).collect();
+    }
+
+    // Reorder the guesses to match a file written by write_guess_order,
+    // rather than recalculating the order. The file must list exactly
+    // the current guesses.
+    pub fn load_guess_order(&mut self, path: impl AsRef<Path>) -> Result<(), WordleError> {
+        let path = path.as_ref();
+        let words = read_word_list(path)?;
+        let bad_order = || WordleError::BadGuessOrder {
+            source: format!("'{}'", path.display()),
+        };
+        if words.len() != self.guesses.len() {
+            return Err(bad_order());
+        }
+
+        // Guesses may be repeated, so hand out each word's indices in turn.
+        let mut indices: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (idx, guess) in self.guesses.iter().enumerate().rev() {
+            indices.entry(guess.as_str()).or_default().push(idx);
+        }
+        let order = words
+            .iter()
+            .map(|word| indices.get_mut(word.as_str()).and_then(|idxs| idxs.pop()))
+            .collect::<Option<Vec<usize>>>()
+            .ok_or_else(bad_order)?;
+
+        self.reorder_guesses(&order);
+        Ok(())
+    }
+
+    // Stop making the given guesses, e.g. words banned by a variant.
+    // Words that are also answers have to stay guessable, as the
+    // solvers guess the answer once they've found it, so those are kept
+    // and returned. Words that aren't guesses are ignored.
+    pub fn block_guesses(&mut self, blocked: &[String]) -> Vec<String> {
+        let blocked = blocked.iter().map(|word| word.as_str()).collect::<HashSet<_>>();
+        let mut kept = Vec::new();
+        let mut order = Vec::new();
+        for (idx, guess) in self.guesses.iter().enumerate() {
+            if !blocked.contains(guess.as_str()) {
+                order.push(idx);
+            } else if self.answer_index(guess).is_some() {
+                if !kept.contains(guess) {
+                    kept.push(guess.clone());
+                }
+                order.push(idx);
+            }
+        }
+        self.reorder_guesses(&order);
+        kept
+    }
+
+    // As block_guesses, for the words listed in a file, one per line.
+    pub fn block_guesses_from(&mut self, path: impl AsRef<Path>) -> Result<Vec<String>, WordleError> {
+        let words = read_word_list(path.as_ref())?;
+        Ok(self.block_guesses(&words))
+    }
+
+    // Write the guesses, in their current order, one per line.
+    pub fn write_guess_order(&self, path: impl AsRef<Path>) -> Result<(), WordleError> {
+        let path = path.as_ref();
+        let mut text = self.guesses.join("\n");
+        text.push('\n');
+        std::fs::write(path, text).map_err(|error| WordleError::Io {
+            path: path.to_path_buf(),
+            error,
+        })
+    }
+
+    // Write the score of every guess against every answer, for loading
+    // into other tools. The file is the number of guesses and then of
+    // answers, each as a little-endian u32, and then the scores (as
+    // encode_score makes them), a row of all the answers for each guess
+    // in turn. Scores are a byte each for words of up to five letters,
+    // and a little-endian u16 each for longer words. In NumPy, for
+    // five-letter words:
+    //
+    //   dims = np.fromfile(path, dtype="<u4", count=2)
+    //   scores = np.fromfile(path, dtype=np.uint8, offset=8).reshape(dims)
+    //
+    // The words go in a sidecar file, the path with ".words" on the
+    // end, in the combined word list format: "[guesses]", the guesses in
+    // row order, then "[answers]" and the answers in column order.
+    pub fn write_score_matrix(&self, path: impl AsRef<Path>) -> Result<(), WordleError> {
+        let path = path.as_ref();
+        let mut data = Vec::with_capacity(8 + self.guesses.len() * self.answers.len());
+        data.extend_from_slice(&(self.guesses.len() as u32).to_le_bytes());
+        data.extend_from_slice(&(self.answers.len() as u32).to_le_bytes());
+        let wide = self.num_buckets() > 256;
+        for guess in 0..self.guesses.len() {
+            for score in self.scores(guess).iter() {
+                if wide {
+                    data.extend_from_slice(&score.to_le_bytes());
+                } else {
+                    data.push(*score as u8);
+                }
+            }
+        }
+
+        let mut words_path = path.as_os_str().to_owned();
+        words_path.push(".words");
+        let words_path = PathBuf::from(words_path);
+        let words = format!("[guesses]\n{}\n[answers]\n{}\n", self.guesses.join("\n"), self.answers.join("\n"));
+
+        std::fs::write(path, data).map_err(|error| WordleError::Io {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        std::fs::write(&words_path, words).map_err(|error| WordleError::Io {
+            path: words_path.clone(),
+            error,
+        })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////
+// Narrowing down the candidate answers
+//
+
+// A pattern of the letters allowed in each position, e.g. ".ight",
+// where '.' is any letter. A position may also be a class of letters,
+// "[aeiou]", or all but a class, "[^xyz]", e.g. "[aeiou]..[^xyz].".
+// Letters are those of the word lists' alphabet.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Pattern {
+    alphabet: Alphabet,
+    // Bitmask per position of the letters allowed there.
+    allowed: Vec<u64>,
+}
+
+impl Pattern {
+    // Parse a pattern over the letters of the alphabet, which may have
+    // up to 64. Letters outside it are in none of the words, so are
+    // never matched.
+    pub fn parse(s: &str, alphabet: &Alphabet) -> Result<Pattern, WordleError> {
+        let invalid = |message: &str| WordleError::InvalidInput(format!("{} in pattern '{}'", message, s));
+        if alphabet.len() > constraints::MAX_LETTERS {
+            return Err(WordleError::InvalidInput(format!(
+                "Patterns handle at most {} different letters, but the words use {}",
+                constraints::MAX_LETTERS,
+                alphabet.len()
+            )));
+        }
+        let any_letter = if alphabet.len() == 64 { u64::MAX } else { (1 << alphabet.len()) - 1 };
+        let is_letter = |c: u8| alphabet.contains(c) || c.is_ascii_lowercase();
+        let letter_bit = |c: u8| if alphabet.contains(c) { 1 << alphabet.index(c) } else { 0 };
+
+        let lower = s.to_ascii_lowercase();
+        let mut chars = lower.bytes();
+        let mut positions = Vec::new();
+        while let Some(c) = chars.next() {
+            let allowed = match c {
+                b'.' => any_letter,
+                c if is_letter(c) => letter_bit(c),
+                b'[' => {
+                    let mut class = 0;
+                    let mut negated = false;
+                    let mut first = true;
+                    loop {
+                        match chars.next() {
+                            Some(b']') => break,
+                            Some(b'^') if first => negated = true,
+                            Some(c) if is_letter(c) => class |= letter_bit(c),
+                            Some(c) => {
+                                return Err(invalid(&format!("Unexpected character '{}' in class", c as char)))
+                            }
+                            None => return Err(invalid("Unterminated class")),
+                        }
+                        first = false;
+                    }
+                    if negated {
+                        any_letter & !class
+                    } else {
+                        class
+                    }
+                }
+                _ => return Err(invalid(&format!("Unexpected character '{}'", c as char))),
+            };
+            positions.push(allowed);
+        }
+        if positions.is_empty() || positions.len() > MAX_WORD_LEN {
+            return Err(WordleError::InvalidInput(format!(
+                "Pattern '{}' should be 1 to {} letters long",
+                s, MAX_WORD_LEN
+            )));
+        }
+        Ok(Pattern {
+            alphabet: alphabet.clone(),
+            allowed: positions,
+        })
+    }
+
+    // The length of the words the pattern can match.
+    pub fn word_len(&self) -> usize {
+        self.allowed.len()
+    }
+
+    pub fn matches(&self, word: &[u8]) -> bool {
+        self.allowed.len() == word.len()
+            && self
+                .allowed
+                .iter()
+                .zip(word.iter())
+                .all(|(allowed, c)| self.alphabet.contains(*c) && allowed & (1 << self.alphabet.index(*c)) != 0)
+    }
+}
+
+// Parse the feedback for a 'len'-letter guess, one letter per position:
+// 'g' or 'c' for correct (green), 'y' or 'p' for present (yellow) and
+// 'a' for absent (grey), e.g. "gaaya". Returns the encoded score.
+// Scores already encoded by encode_score can be given as a number
+// instead.
+pub fn parse_score(s: &str, len: usize) -> Result<BucketId, WordleError> {
+    let invalid = WordleError::InvalidInput;
+    if !s.is_empty() && s.bytes().all(|c| c.is_ascii_digit()) {
+        return match s.parse::<usize>() {
+            Ok(n) if n < num_buckets(len) => Ok(n as BucketId),
+            _ => Err(invalid(format!("Encoded feedback '{}' should be below {}", s, num_buckets(len)))),
+        };
+    }
+    if s.len() != len {
+        return Err(invalid(format!("Feedback '{}' should be {} letters long", s, len)));
+    }
+    let scores = s
+        .to_ascii_lowercase()
+        .bytes()
+        .map(|c| match c {
+            b'g' | b'c' => Ok(CharScore::Correct),
+            b'y' | b'p' => Ok(CharScore::Present),
+            b'a' => Ok(CharScore::Absent),
+            _ => Err(invalid(format!("Unexpected character '{}' in feedback '{}'", c as char, s))),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(encode_score(scores.into_iter()))
+}
+
+// Parse a game history of space-separated "guess:feedback" pairs, e.g.
+// "crane:aayaa salet:gaaya", for 'len'-letter words.
+pub fn parse_history(s: &str, len: usize) -> Result<Vec<(String, BucketId)>, WordleError> {
+    s.split_whitespace()
+        .map(|entry| match entry.split_once(':') {
+            Some((guess, score)) => Ok((guess.to_ascii_lowercase(), parse_score(score, len)?)),
+            None => Err(WordleError::InvalidInput(format!(
+                "Expected 'guess:feedback', got '{}'",
+                entry
+            ))),
+        })
+        .collect()
+}
+
+impl Scorer {
+    pub fn guess_index(&self, word: &str) -> Option<usize> {
+        self.guess_indices.get(word).copied()
+    }
+
+    pub fn answer_index(&self, word: &str) -> Option<usize> {
+        self.answer_indices.get(word).copied()
+    }
+
+    // Read a file of answers, returning their indices, sorted, for use
+    // as a set of candidates.
+    pub fn read_candidates(&self, path: impl AsRef<Path>) -> Result<Vec<usize>, WordleError> {
+        let path = path.as_ref();
+        let mut candidates = read_word_list(path)?
+            .into_iter()
+            .map(|word| {
+                let word = word.to_ascii_lowercase();
+                self.answer_index(&word).ok_or_else(|| WordleError::UnknownAnswer {
+                    word,
+                    source: format!("'{}'", path.display()),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        candidates.sort_unstable();
+        candidates.dedup();
+        Ok(candidates)
+    }
+
+    // As read_candidates, but skipping any word that isn't an answer
+    // (including words of the wrong length) rather than failing, and
+    // returning those too. Failing to read the file is still an error.
+    pub fn read_candidates_skipping_unknown(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<(Vec<usize>, Vec<String>), WordleError> {
+        let mut candidates = Vec::new();
+        let mut skipped = Vec::new();
+        for word in read_lines(path.as_ref())? {
+            let word = word.trim().to_ascii_lowercase();
+            match self.answer_index(&word) {
+                Some(answer) => candidates.push(answer),
+                None => skipped.push(word),
+            }
+        }
+        candidates.sort_unstable();
+        candidates.dedup();
+        Ok((candidates, skipped))
+    }
+
+    // Is the word in the guess list?
+    pub fn is_valid_guess(&self, word: &str) -> bool {
+        self.guess_indices.contains_key(word)
+    }
+
+    // Is the word in the answer list?
+    pub fn is_possible_answer(&self, word: &str) -> bool {
+        self.answer_indices.contains_key(word)
+    }
+
+    // Could any answer give this feedback for the guess? The guess
+    // needn't be in the word list, but must be the right length.
+    pub fn is_feedback_possible(&self, guess: &str, score: BucketId) -> bool {
+        guess.len() == self.word_len
+            && self
+                .answers
+                .iter()
+                .any(|answer| score_with_rules(self.rules, guess.as_bytes(), answer.as_bytes()) == score)
+    }
+
+    // Keep only the candidates that would have produced the given score
+    // for the given guess.
+    pub fn filter_candidates(&self, guess: usize, score: BucketId, candidates: &[usize]) -> Vec<usize> {
+        candidates
+            .iter()
+            .filter(|a| self.score(guess, **a) == score)
+            .copied()
+            .collect()
+    }
+
+    // As filter_candidates, checking that the answer is still among the
+    // candidates left: if the target is known (e.g. when self-solving),
+    // that it's kept, and otherwise that something is. If not, the score
+    // was wrong, from mistyped feedback or a bug in scoring.
+    pub fn filter_candidates_checked(
+        &self,
+        guess: usize,
+        score: BucketId,
+        candidates: &[usize],
+        target: Option<usize>,
+    ) -> Result<Vec<usize>, WordleError> {
+        let filtered = self.filter_candidates(guess, score, candidates);
+        match target {
+            Some(target) if !filtered.contains(&target) => Err(WordleError::InconsistentScore {
+                guess: self.guesses[guess].clone(),
+                answer: self.answers[target].clone(),
+            }),
+            _ if filtered.is_empty() => Err(WordleError::NoCandidates {
+                guess: self.guesses[guess].clone(),
+            }),
+            _ => Ok(filtered),
+        }
+    }
+
+    // The number of candidates consistent with just some letters of a
+    // guess being green, to preview a guess live as it's typed: 'greens'
+    // has the letter for each position fixed so far, and None for the
+    // rest. Positions past the end of 'greens' are unconstrained.
+    pub fn count_green_matches(&self, greens: &[Option<u8>], candidates: &[usize]) -> usize {
+        candidates
+            .iter()
+            .filter(|a| {
+                greens
+                    .iter()
+                    .zip(self.answers[**a].bytes())
+                    .all(|(green, c)| green.is_none_or(|g| g == c))
+            })
+            .count()
+    }
+
+    // Narrow the candidates down using each (guess, score) in a history.
+    pub fn apply_history(
+        &self,
+        history: &[(String, BucketId)],
+        candidates: &[usize],
+    ) -> Result<Vec<usize>, WordleError> {
+        let mut candidates = candidates.to_vec();
+        for (word, score) in history.iter() {
+            let guess = self
+                .guess_index(word)
+                .ok_or_else(|| WordleError::UnknownGuess { word: word.clone() })?;
+            candidates = self.filter_candidates(guess, *score, &candidates);
+        }
+        Ok(candidates)
+    }
+}
+
+////////////////////////////////////////////////////////////////////////
+// Greedy guess selection
+//
+
+impl Scorer {
+    // All the guesses that tie for the best ranking against the
+    // candidate answers, in guess order (or input order, with
+    // prefer_input_order). If only one candidate remains, it's the only
+    // sensible guess.
+    pub fn best_guesses(&self, candidates: &[usize], ranking: Ranking) -> Vec<usize> {
+        self.best_guesses_excluding(candidates, ranking, &[])
+    }
+
+    // As best_guesses, but never picking any of the excluded guesses,
+    // e.g. those already made this game.
+    pub fn best_guesses_excluding(&self, candidates: &[usize], ranking: Ranking, excluded: &[usize]) -> Vec<usize> {
+        match candidates {
+            [] => Vec::new(),
+            [answer] => self.guess_index(&self.answers[*answer]).into_iter().collect(),
+            _ => {
+                let excluded = excluded.iter().collect::<HashSet<_>>();
+                let ranks = (0..self.guesses.len())
+                    .filter(|guess| !excluded.contains(guess))
+                    .map(|guess| (guess, self.rank_guess(ranking, guess, candidates)))
+                    .collect::<Vec<(usize, Rank)>>();
+                let best = match ranks.iter().map(|(_, rank)| *rank).min_by(|a, b| a.partial_cmp(b).unwrap()) {
+                    Some(best) => best,
+                    None => return Vec::new(),
+                };
+                let mut best = ranks
+                    .iter()
+                    .filter(|(_, rank)| *rank == best)
+                    .map(|(guess, _)| *guess)
+                    .collect::<Vec<_>>();
+                if self.prefer_input_order {
+                    best.sort_by_key(|guess| self.input_order[*guess]);
+                }
+                best
+            }
+        }
+    }
+
+    // Pick the guess that does best under the given ranking against the
+    // candidate answers. Ties are broken in favour of guesses that could
+    // be the answer, and then as in best_guesses. Small candidate sets (see
+    // endgame_limit) may be solved exactly instead.
+    pub fn best_guess(&self, candidates: &[usize], ranking: Ranking) -> Option<usize> {
+        self.best_guess_excluding(candidates, ranking, &[])
+    }
+
+    // As best_guess, but never picking any of the excluded guesses. A
+    // guess already made gives the same feedback for every candidate
+    // left, so the exact endgame search never picks one anyway.
+    pub fn best_guess_excluding(&self, candidates: &[usize], ranking: Ranking, excluded: &[usize]) -> Option<usize> {
+        if candidates.len() > 1 && candidates.len() <= self.endgame_limit {
+            return Some(OptimalSolver::new(self).solve(candidates).1);
+        }
+        self.best_ranked_guess(candidates, ranking, excluded)
+    }
+
+    // As best_guess_excluding, without the exact endgame.
+    fn best_ranked_guess(&self, candidates: &[usize], ranking: Ranking, excluded: &[usize]) -> Option<usize> {
+        if candidates.len() > 1 && candidates.len() <= self.answer_endgame_limit {
+            return self.best_answer_guess(candidates, ranking, excluded);
+        }
+        let best = self.best_guesses_excluding(candidates, ranking, excluded);
+        let candidate_words = candidates
+            .iter()
+            .map(|a| self.answers[*a].as_str())
+            .collect::<HashSet<&str>>();
+        best.iter()
+            .find(|guess| candidate_words.contains(self.guesses[**guess].as_str()))
+            .or_else(|| best.first())
+            .copied()
+    }
+
+    // Could the guess be made in hard mode after the (guess, score)
+    // history? Every green must be reused in place, and every yellow
+    // somewhere, with a letter that's green or yellow more than once in
+    // a guess needed at least that many times.
+    pub fn is_hard_mode_legal(&self, history: &[(usize, BucketId)], guess: usize) -> bool {
+        self.history_constraints(history).allows_in_hard_mode(self.guesses[guess].as_bytes())
+    }
+
+    fn history_constraints(&self, history: &[(usize, BucketId)]) -> Constraints {
+        let mut constraints = Constraints::with_alphabet(&self.alphabet, self.word_len);
+        for (guess, score) in history.iter() {
+            constraints.add(self.guesses[*guess].as_bytes(), *score);
+        }
+        constraints
+    }
+
+    // The guesses not to make in hard mode after the history: those
+    // already made, and those the feedback rules out.
+    pub fn hard_mode_excluded(&self, history: &[(usize, BucketId)]) -> Vec<usize> {
+        let constraints = self.history_constraints(history);
+        (0..self.guesses.len())
+            .filter(|guess| {
+                history.iter().any(|(made, _)| made == guess)
+                    || !constraints.allows_in_hard_mode(self.guesses[*guess].as_bytes())
+            })
+            .collect()
+    }
+
+    // As best_guess_excluding, only picking guesses allowed in hard mode
+    // after the history. The exact endgame search can't be restricted
+    // to those guesses, so endgame_limit (--endgame) is ignored, but the
+    // answer endgame still applies, as the candidates are always legal.
+    pub fn best_hard_mode_guess(
+        &self,
+        candidates: &[usize],
+        ranking: Ranking,
+        history: &[(usize, BucketId)],
+    ) -> Option<usize> {
+        self.best_ranked_guess(candidates, ranking, &self.hard_mode_excluded(history))
+    }
+
+    // The n best guesses against the candidates, with their metrics side
+    // by side, best first. They're sorted by worst case, then expected
+    // remaining, then guess order.
+    pub fn top_guesses(&self, candidates: &[usize], n: usize) -> Vec<GuessEval> {
+        let mut evals = (0..self.guesses.len())
+            .map(|guess| self.evaluate_guess(guess, candidates))
+            .collect::<Vec<_>>();
+        evals.sort_by(|a, b| {
+            a.worst_case
+                .cmp(&b.worst_case)
+                .then(a.expected_remaining.partial_cmp(&b.expected_remaining).unwrap())
+        });
+        evals.truncate(n);
+        evals
+    }
+
+    // All the metrics for a guess, from one pass over its buckets.
+    pub fn evaluate_guess(&self, guess: usize, candidates: &[usize]) -> GuessEval {
+        let mut worst_case = 0;
+        let mut num_buckets = 0;
+        let mut sum_of_squares = 0;
+        self.for_each_bucket(guess, candidates, |_, members| {
+            worst_case = worst_case.max(members.len());
+            num_buckets += 1;
+            sum_of_squares += members.len() * members.len();
+        });
+        GuessEval {
+            word: self.guesses[guess].clone(),
+            worst_case,
+            num_buckets,
+            expected_remaining: if candidates.is_empty() {
+                0.0
+            } else {
+                sum_of_squares as f64 / candidates.len() as f64
+            },
+        }
+    }
+
+    // Once 'limit' or fewer candidates remain, have best_guess only
+    // guess answers, using a compact answers x answers score matrix.
+    pub fn use_answer_endgame(&mut self, limit: usize) {
+        self.answer_endgame_limit = limit;
+        self.answer_matrix = if limit > 0 {
+            score_rows_with_rules(self.rules, &self.answers, &self.answers).collect()
+        } else {
+            Vec::new()
+        };
+    }
+
+    // The best of the answers to guess, as a guess index, breaking ties
+    // as best_guess does.
+    fn best_answer_guess(&self, candidates: &[usize], ranking: Ranking, excluded: &[usize]) -> Option<usize> {
+        let mut is_candidate = vec![false; self.answers.len()];
+        for answer in candidates.iter() {
+            is_candidate[*answer] = true;
+        }
+        let excluded = excluded.iter().collect::<HashSet<_>>();
+        (0..self.answers.len())
+            .filter_map(|answer| {
+                let guess = self.guess_index(&self.answers[answer])?;
+                if excluded.contains(&guess) {
+                    return None;
+                }
+                let row = &self.answer_matrix[answer];
+                let rank = match ranking {
+                    Ranking::ResidualWorstCase => self.rank_guess(ranking, guess, candidates),
+                    _ => rank_by(ranking, self.num_buckets(), candidates, |other| row[other]),
+                };
+                Some((rank, !is_candidate[answer], guess))
+            })
+            .min_by(|a, b| a.partial_cmp(b).unwrap())
+            .map(|(_, _, guess)| guess)
+    }
+
+    // Play a game against the given target answer, greedily making the
+    // best guess each time. Returns the number of guesses taken.
+    pub fn guess_greedily(&self, target: usize, candidates: &[usize], ranking: Ranking) -> usize {
+        self.greedy_guesses(target, candidates, ranking).len()
+    }
+
+    // As guess_greedily, but returns the guesses made, ending with the
+    // target.
+    pub fn greedy_guesses(&self, target: usize, candidates: &[usize], ranking: Ranking) -> Vec<usize> {
+        self.greedy_guesses_after(target, candidates, ranking, &[])
+    }
+
+    // The guess the greedy strategy makes next, legal in hard mode given
+    // the history so far.
+    fn greedy_guess(&self, candidates: &[usize], ranking: Ranking, history: &[(usize, BucketId)]) -> Option<usize> {
+        if self.hard_mode {
+            self.best_hard_mode_guess(candidates, ranking, history)
+        } else {
+            self.best_guess(candidates, ranking)
+        }
+    }
+
+    // As greedy_guesses, after the guesses already made, which in hard
+    // mode constrain the guesses that follow.
+    fn greedy_guesses_after(&self, target: usize, candidates: &[usize], ranking: Ranking, made: &[usize]) -> Vec<usize> {
+        let mut candidates = candidates.to_vec();
+        let mut history = made.iter().map(|guess| (*guess, self.score(*guess, target))).collect::<Vec<_>>();
+        let mut guesses = Vec::new();
+        loop {
+            let guess = self.greedy_guess(&candidates, ranking, &history).unwrap();
+            guesses.push(guess);
+            if self.guesses[guess] == self.answers[target] {
+                return guesses;
+            }
+
+            let score = self.score(guess, target);
+            candidates = self.filter_candidates_checked(guess, score, &candidates, Some(target)).unwrap();
+            history.push((guess, score));
+        }
+    }
+
+    // The expected number of guesses guess_greedily takes, when the
+    // target is drawn from the candidates with the given weights (one
+    // per candidate, not necessarily summing to 1).
+    pub fn expected_guesses(&self, candidates: &[usize], weights: &[f64], ranking: Ranking) -> f64 {
+        self.expected_guesses_after(candidates, weights, ranking, &mut Vec::new())
+    }
+
+    // As expected_guesses, after the history so far, which in hard mode
+    // constrains the guesses that follow.
+    fn expected_guesses_after(
+        &self,
+        candidates: &[usize],
+        weights: &[f64],
+        ranking: Ranking,
+        history: &mut Vec<(usize, BucketId)>,
+    ) -> f64 {
+        assert_eq!(candidates.len(), weights.len());
+        let total: f64 = weights.iter().sum();
+        if candidates.is_empty() || total <= 0.0 {
+            return 0.0;
+        }
+
+        let guess = self.greedy_guess(candidates, ranking, history).unwrap();
+        let weight_of = candidates
+            .iter()
+            .zip(weights.iter())
+            .map(|(c, w)| (*c, *w))
+            .collect::<HashMap<usize, f64>>();
+        let mut expected = 1.0;
+        for bucket in self.bucket_answers(guess, candidates) {
+            let bucket_weights = bucket.iter().map(|c| weight_of[c]).collect::<Vec<_>>();
+            let solved = bucket.len() == 1 && self.answers[bucket[0]] == self.guesses[guess];
+            if !solved {
+                let p = bucket_weights.iter().sum::<f64>() / total;
+                history.push((guess, self.score(guess, bucket[0])));
+                expected += p * self.expected_guesses_after(&bucket, &bucket_weights, ranking, history);
+                history.pop();
+            }
+        }
+        expected
+    }
+
+    // The candidate a typical player might guess: the one whose distinct
+    // letters appear in the most candidates. Returns the guess index.
+    pub fn most_common_letters_candidate(&self, candidates: &[usize]) -> Option<usize> {
+        let letters = |word: &str| {
+            let mut letters = word.bytes().collect::<Vec<_>>();
+            letters.sort_unstable();
+            letters.dedup();
+            letters
+        };
+        let mut frequency: HashMap<u8, usize> = HashMap::new();
+        for answer in candidates.iter() {
+            for c in letters(&self.answers[*answer]) {
+                *frequency.entry(c).or_insert(0) += 1;
+            }
+        }
+        let mut best: Option<(usize, usize)> = None;
+        for answer in candidates.iter() {
+            let score = letters(&self.answers[*answer]).iter().map(|c| frequency[c]).sum();
+            if best.is_none_or(|(best_score, _)| score > best_score) {
+                best = Some((score, *answer));
+            }
+        }
+        best.and_then(|(_, answer)| self.guess_index(&self.answers[answer]))
+    }
+
+    // Play a game like a typical player: only ever guess candidates,
+    // picked by most_common_letters_candidate. Returns the number of
+    // guesses taken.
+    pub fn guess_like_human(&self, target: usize, candidates: &[usize]) -> usize {
+        self.human_guesses(target, candidates).len()
+    }
+
+    // As guess_like_human, but returns the guesses made, ending with
+    // the target.
+    pub fn human_guesses(&self, target: usize, candidates: &[usize]) -> Vec<usize> {
+        let mut candidates = candidates.to_vec();
+        let mut guesses = Vec::new();
+        loop {
+            let guess = self.most_common_letters_candidate(&candidates).unwrap();
+            guesses.push(guess);
+            if self.guesses[guess] == self.answers[target] {
+                return guesses;
+            }
+            candidates = self
+                .filter_candidates_checked(guess, self.score(guess, target), &candidates, Some(target))
+                .unwrap();
+        }
+    }
+
+    // The greedy second guess for each feedback the opener can get over
+    // all the answers, worked out in parallel. Feedback no answer gives,
+    // and the opener being right, have no second guess, so aren't
+    // included.
+    pub fn second_guesses(&self, opener: usize, ranking: Ranking) -> HashMap<BucketId, usize> {
+        let answers = (0..self.answers.len()).collect::<Vec<_>>();
+        self.bucket_answers(opener, &answers)
+            .into_par_iter()
+            .map(|bucket| (self.score(opener, bucket[0]), bucket))
+            .filter(|(score, _)| *score != self.correct_score())
+            .map(|(score, bucket)| (score, self.greedy_guess(&bucket, ranking, &[(opener, score)]).unwrap()))
+            .collect()
+    }
+
+    // The guess solve_all opens with if not given an opener.
+    pub fn first_guess(&self, answers: &[usize], ranking: Ranking, human: bool) -> usize {
+        if human {
+            self.most_common_letters_candidate(answers).unwrap()
+        } else {
+            self.best_guess(answers, ranking).unwrap()
+        }
+    }
+
+    // A random opener from the best quarter of the guesses under the
+    // ranking, for variety while still playing well.
+    pub fn random_opener(&self, answers: &[usize], ranking: Ranking, rng: &mut Rng) -> Result<usize, WordleError> {
+        let metrics = self.guess_metrics(answers)?;
+        let mut ranked = (0..self.guesses.len())
+            .map(|guess| (metrics.rank(ranking, guess), guess))
+            .collect::<Vec<_>>();
+        ranked.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let quartile = ranked.len().div_ceil(4);
+        Ok(ranked[rng.below(quartile)].1)
+    }
+
+    // The answers, hardest first, judged by how many answers share their
+    // bucket after the opener.
+    pub fn answers_by_difficulty(&self, opener: usize, answers: &[usize]) -> Vec<usize> {
+        let mut sized = Vec::with_capacity(answers.len());
+        self.for_each_bucket(opener, answers, |_, members| {
+            sized.extend(members.iter().map(|answer| (std::cmp::Reverse(members.len()), *answer)));
+        });
+        sized.sort_unstable();
+        sized.into_iter().map(|(_, answer)| answer).collect()
+    }
+
+    // Solve every one of the answers, returning the guesses made for
+    // each. The first guess is the opener if given, or the strategy's
+    // choice. If 'human' is set, play like a typical player rather than
+    // greedily by the ranking.
+    pub fn solve_all(
+        &self,
+        answers: &[usize],
+        ranking: Ranking,
+        human: bool,
+        opener: Option<usize>,
+    ) -> Vec<SolveResult> {
+        self.solve_all_with_progress(answers, ranking, human, opener, 1)
+    }
+
+    // As solve_all, only logging the progress on every 'progress_every'
+    // answers, or never if it's 0.
+    pub fn solve_all_with_progress(
+        &self,
+        answers: &[usize],
+        ranking: Ranking,
+        human: bool,
+        opener: Option<usize>,
+        progress_every: usize,
+    ) -> Vec<SolveResult> {
+        let first_guess = opener.unwrap_or_else(|| self.first_guess(answers, ranking, human));
+        self.solve_all_with_openers(answers, ranking, human, &[first_guess], progress_every)
+    }
+
+    // As solve_all_with_progress, always opening with the given guesses
+    // in turn, whatever their feedback, before solving by the strategy.
+    // Play stops early if one of them is the answer.
+    pub fn solve_all_with_openers(
+        &self,
+        answers: &[usize],
+        ranking: Ranking,
+        human: bool,
+        openers: &[usize],
+        progress_every: usize,
+    ) -> Vec<SolveResult> {
+        // The opening moves are the same for every answer, so work out
+        // the buckets they produce just once.
+        let mut bucket_of = vec![0; self.answers.len()];
+        let buckets = self.partition_by_guesses(openers, answers);
+        for (idx, bucket) in buckets.iter().enumerate() {
+            for answer in bucket.iter() {
+                bucket_of[*answer] = idx;
+            }
+        }
+
+        // Logging is best-effort, so write errors are ignored.
+        let stderr = io::stderr();
+        let mut log_output = BufWriter::new(stderr.lock());
+        let mut next_flush = 0;
+        let results = answers
+            .iter()
+            .enumerate()
+            .map(|(idx, target)| {
+                let log = progress_every > 0 && idx % progress_every == 0;
+                if log {
+                    let _ = writeln!(
+                        log_output,
+                        "Trying to greedily solve {} ({} of {})",
+                        self.answers[*target],
+                        idx + 1,
+                        answers.len()
+                    );
+                }
+                let mut guesses = Vec::new();
+                let mut solved = false;
+                for opener in openers.iter() {
+                    guesses.push(*opener);
+                    solved = self.guesses[*opener] == self.answers[*target];
+                    if solved {
+                        break;
+                    }
+                }
+                if !solved {
+                    let bucket = &buckets[bucket_of[*target]];
+                    if human {
+                        guesses.extend(self.human_guesses(*target, bucket));
+                    } else {
+                        guesses.extend(self.greedy_guesses_after(*target, bucket, ranking, &guesses));
+                    }
+                }
+                if log {
+                    let _ = writeln!(log_output, "Took {} guesses", guesses.len());
+                    if idx >= next_flush {
+                        let _ = log_output.flush();
+                        next_flush = idx + self.log_batch;
+                    }
+                }
+                SolveResult {
+                    answer: *target,
+                    guesses,
+                }
+            })
+            .collect();
+        let _ = log_output.flush();
+        results
+    }
+}
+
+// How a guess does against a set of candidates, by each metric.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GuessEval {
+    pub word: String,
+    // Size of the largest bucket.
+    pub worst_case: usize,
+    pub num_buckets: usize,
+    pub expected_remaining: f64,
+}
+
+// The result of solving one answer.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SolveResult {
+    pub answer: usize,
+    // Guess indices, ending with the answer.
+    pub guesses: Vec<usize>,
+}
+
+impl SolveResult {
+    pub fn num_guesses(&self) -> usize {
+        self.guesses.len()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////
+// Views onto a subset of the candidate answers
+//
+
+// A Scorer restricted to a subset of the candidate answers, sharing the
+// parent's guesses and score cache rather than rebuilding them.
+pub struct CandidateView<'a> {
+    scorer: &'a Scorer,
+    candidates: Vec<usize>,
+}
+
+impl Scorer {
+    pub fn clone_for_candidates(&self, candidates: &[usize]) -> CandidateView<'_> {
+        CandidateView {
+            scorer: self,
+            candidates: candidates.to_vec(),
+        }
+    }
+}
+
+impl<'a> CandidateView<'a> {
+    pub fn scorer(&self) -> &'a Scorer {
+        self.scorer
+    }
+
+    pub fn candidates(&self) -> &[usize] {
+        &self.candidates
+    }
+
+    pub fn bucket_answers(&self, guess: usize) -> Vec<Vec<usize>> {
+        self.scorer.bucket_answers(guess, &self.candidates)
+    }
+
+    pub fn best_guess(&self, ranking: Ranking) -> Option<usize> {
+        self.scorer.best_guess(&self.candidates, ranking)
+    }
+
+    // A further-restricted view, keeping the candidates that would give
+    // the score for the guess.
+    pub fn narrow(&self, guess: usize, score: BucketId) -> CandidateView<'a> {
+        CandidateView {
+            scorer: self.scorer,
+            candidates: self.scorer.filter_candidates(guess, score, &self.candidates),
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////
+// Bounded strategy search
+//
+
+// Upper bound on the number of answers that can be told apart within
+// 'guesses' guesses, counting the final correct guess, with
+// 'num_buckets' possible scores.
+fn max_distinguishable(num_buckets: usize, guesses: usize) -> usize {
+    (0..guesses).fold(0, |acc: usize, _| acc.saturating_mul(num_buckets - 1).saturating_add(1))
+}
+
+type StrategyMemo = HashMap<(Vec<usize>, usize), Option<usize>>;
+
+// The state of a bounded strategy search: what's been found so far,
+// and, if limited, how many more candidate sets it may visit.
+struct StrategySearch {
+    memo: StrategyMemo,
+    nodes: usize,
+    budget: Option<usize>,
+}
+
+impl StrategySearch {
+    fn new(budget: Option<usize>) -> StrategySearch {
+        StrategySearch {
+            memo: HashMap::new(),
+            nodes: 0,
+            budget,
+        }
+    }
+
+    fn out_of_budget(&self) -> bool {
+        self.budget.is_some_and(|budget| self.nodes >= budget)
+    }
+}
+
+// The minimax strategy for a set of answers, from solve_optimally.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MinimaxSolution {
+    // The most guesses it takes, counting the final correct guess.
+    pub worst_case: usize,
+    // The average over the answers, each equally likely, of the
+    // strategy found. Other strategies with the same worst case may
+    // have a lower average, so this isn't the best average within it.
+    pub average: f64,
+    pub first_guess: usize,
+}
+
+impl Scorer {
+    // Is there a strategy that always finds the answer within 'max'
+    // guesses, counting the final correct guess? If so, returns a
+    // first guess that achieves it.
+    pub fn exists_strategy_within(&self, max: usize) -> Option<usize> {
+        let answers = (0..self.answers.len()).collect::<Vec<usize>>();
+        self.strategy_within(&answers, max)
+    }
+
+    // As exists_strategy_within, for a (sorted) subset of the answers.
+    pub fn strategy_within(&self, answers: &[usize], max: usize) -> Option<usize> {
+        self.strategy_within_memo(answers, max, &mut StrategySearch::new(None))
+    }
+
+    // The minimax strategy for a (sorted) subset of the answers: the one
+    // needing the fewest guesses in the worst case, if that's within
+    // 'max' guesses, e.g. Wordle's six. With a budget, the search gives
+    // up after visiting that many candidate sets. Also returns whether
+    // the result is proven: if not, None only means none was found.
+    pub fn solve_optimally(
+        &self,
+        answers: &[usize],
+        max: usize,
+        budget: Option<usize>,
+    ) -> (Option<MinimaxSolution>, bool) {
+        // Deepen a guess at a time, sharing the memo between depths, so
+        // the first depth that works is the minimum.
+        let mut search = StrategySearch::new(budget);
+        let solution = (1..=max).find_map(|depth| {
+            let first_guess = self.strategy_within_memo(answers, depth, &mut search)?;
+            let total = self.strategy_total(answers, depth, &mut search);
+            Some(MinimaxSolution {
+                worst_case: depth,
+                average: total as f64 / answers.len() as f64,
+                first_guess,
+            })
+        });
+        (solution, !search.out_of_budget())
+    }
+
+    // The total number of guesses to solve each of the answers with the
+    // strategy strategy_within_memo found within 'max' guesses.
+    fn strategy_total(&self, answers: &[usize], max: usize, search: &mut StrategySearch) -> usize {
+        if answers.len() == 1 {
+            return 1;
+        }
+        // Already found, so this just looks it up.
+        let guess = self.strategy_within_memo(answers, max, search).unwrap();
+        answers.len()
+            + self
+                .bucket_answers(guess, answers)
+                .iter()
+                .filter(|bucket| !(bucket.len() == 1 && self.answers[bucket[0]] == self.guesses[guess]))
+                .map(|bucket| self.strategy_total(bucket, max - 1, search))
+                .sum::<usize>()
+    }
+
+    fn strategy_within_memo(
+        &self,
+        answers: &[usize],
+        max: usize,
+        search: &mut StrategySearch,
+    ) -> Option<usize> {
+        if answers.is_empty() || answers.len() > max_distinguishable(self.num_buckets(), max) {
+            return None;
+        }
+        if let [answer] = answers {
+            return self.guess_index(&self.answers[*answer]);
+        }
+
+        let key = (answers.to_vec(), max);
+        if let Some(result) = search.memo.get(&key) {
+            return *result;
+        }
+        if search.out_of_budget() {
+            return None;
+        }
+        search.nodes += 1;
+
+        // Try the best splitters first, skipping any guess that leaves a
+        // bucket too big to finish off in time.
+        let limit = max_distinguishable(self.num_buckets(), max - 1);
+        let mut guesses = (0..self.guesses.len())
+            .map(|guess| (self.find_greedy_worst_case(guess, answers), guess))
+            .filter(|(worst_case, _)| *worst_case <= limit && *worst_case < answers.len())
+            .collect::<Vec<_>>();
+        guesses.sort();
+
+        let result = guesses.into_iter().map(|(_, guess)| guess).find(|guess| {
+            self.bucket_answers(*guess, answers).iter().all(|bucket| {
+                // The bucket where the guess was the answer is already done.
+                let solved = bucket.len() == 1 && self.answers[bucket[0]] == self.guesses[*guess];
+                solved || self.strategy_within_memo(bucket, max - 1, search).is_some()
+            })
+        });
+
+        // A failure after the budget ran out may just be the search
+        // giving up, so only remember it if it's certain.
+        if result.is_some() || !search.out_of_budget() {
+            search.memo.insert(key, result);
+        }
+        result
+    }
+}
+
+////////////////////////////////////////////////////////////////////////
+// Depth-first search solver, biased towards trying best splitters first.
+//
+
+// Specialise last layers of search as an optimisation.
+
+// Allocated once to optimise leaf case, with room for the longest words.
+pub static mut SEEN_TABLE: &mut [u8] = &mut [0; num_buckets(MAX_WORD_LEN)];
+pub static mut COUNTER: u8 = 0;
+
+// Can we solve with 2 guesses? 2nd guess must be correct answer, which
+// means all we need to do is check that the first guess full determines
+// - there can be at most one possible solution per bucket.
+fn can_solve2(s: &Scorer, answers: &[usize]) -> bool {
+    (0..s.guesses.len()).any(|guess| can_solve_with_guess2(s, guess, answers))
+}
+
+fn can_solve_with_guess2(
+    s: &Scorer,
+    guess: usize,
+    answers: &[usize]
+) -> bool {
+    unsafe {
+        // Special case - next guess has to be final, so check if each bucket
+        // contains at most one entry.
+        //
+        // Set counter to a value not seen in the array.
+        if COUNTER == u8::MAX {
+            COUNTER = 0;
+            (*SEEN_TABLE).fill(255);
+        } else {
+            COUNTER += 1;
+        }
+
+        // Iterate over the answers, early-outing if a bucket is used twice.
+        for answer in answers.iter() {
+            let score = s.score(guess, *answer);
+            if SEEN_TABLE[score as usize] == COUNTER {
+                return false;
+            }
+            SEEN_TABLE[score as usize] = COUNTER;
+        }
+        true
+    }
+}
+
+fn can_solve3(
+    s: &mut Scorer,
+    answers: &[usize]
+) -> bool {
+    (0..s.guesses.len())
+        .any(|guess| {
+            s.bucket_answers3(guess, answers);
+            s.bucket_vec.iter().all(|v| {
+                v.is_empty() || can_solve2(s, v)
+            })
+        })
+}
+
+// General solver
+//
+// Can we, in the given number of guesses, uniquely identify the
+// solution from the given answer list? Guesses should be sorted to
+// put best splitters first to make finding answers faster.
+pub fn can_solve(
+    s: &mut Scorer,
+    num_guesses: usize,
+    answers: &[usize]
+) -> bool {
+    if num_guesses == 3 {
+        return can_solve3(s, answers)
+    } else if num_guesses == 2 {
+        return can_solve2(s, answers)
+    }
+
+    for idx in 0..s.guesses.len() {
+        eprint!(
+            " {:5} {:5}/{:5}\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08",
+            s.guesses[idx],
+            idx,
+            s.guesses.len());
+
+            let buckets = s.bucket_answers(idx, answers);
+        if buckets.iter().all(|v| { can_solve(s, num_guesses - 1, v) }) {
+            return true;
+        }
+    }
+    false
+}
+
+////////////////////////////////////////////////////////////////////////
+// Top-level copy of solver, with more diagnostic spam
+//
+
+pub fn can_solve_noisy(
+    s: &mut Scorer,
+    num_guesses: usize,
+    answers: &[usize],
+    shard_index: usize,
+    shard_count: usize,
+) -> bool {
+    for idx in 0..s.guesses.len() {
+        if idx % shard_count != shard_index {
+            continue;
+        }
+        eprintln!("Trying guess {} ({}/{})", s.guesses[idx], idx, s.guesses.len());
+        if can_solve_with_guess_noisy(s, idx, num_guesses, answers) {
+            return true;
+        }
+    }
+    false
+}
+
+fn can_solve_with_guess_noisy(
+    s: &mut Scorer,
+    guess: usize,
+    num_guesses: usize,
+    answers: &[usize]
+) -> bool {
+    let buckets = s.bucket_answers(guess, answers);
+
+    for (idx, bucket) in buckets.iter().enumerate() {
+        eprint!("    Bucket {}/{} (size {})... ", idx, buckets.len(), bucket.len());
+        assert_eq!(num_guesses - 1, 3);
+        let soluble = can_solve(s, num_guesses - 1, bucket);
+        if soluble {
+            eprintln!("solved");
+        } else {
+            eprintln!("insoluble");
+            return false;
+        }
+    }
+    true
+}
+
+// A Scorer for the tests' small word lists, which mostly differ in a
+// letter or two, so the scores are easy to work out by hand.
+#[cfg(test)]
+pub(crate) fn small_scorer(guesses: &[&str], answers: &[&str]) -> Scorer {
+    let strings = |words: &[&str]| words.iter().map(|w| String::from(*w)).collect();
+    Scorer::from_word_lists(strings(guesses), strings(answers)).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: CharScore = CharScore::Absent;
+    const C: CharScore = CharScore::Correct;
+    const P: CharScore = CharScore::Present;
+
+    fn check(guess: &str, answer: &str, score: &[CharScore]) {
+        assert_eq!(
+            score_wordle(guess.as_bytes(), answer.as_bytes()),
+            encode_score(score.iter().cloned())
+        );
+    }
+
+    #[test]
+    fn test_simple_green() {
+        check("weary", "wills", &[C, A, A, A, A]);
+    }
+
+    #[test]
+    fn test_simple_yellow() {
+        check("pilot", "leaks", &[A, A, P, A, A]);
+    }
+
+    #[test]
+    fn test_double_yellow() {
+        check("kazoo", "tools", &[A, A, A, P, P]);
+    }
+
+    #[test]
+    fn test_green_overrides_yellow() {
+        // Letters are 'used up' by exact matches.
+        check("loose", "chore", &[A, A, C, A, C]);
+    }
+
+    #[test]
+    fn test_yellow_overrides_yellow() {
+        // Letters are 'used up' by inexact matches, too.
+        // So, only one 'O' matches.
+        check("spoon", "coats", &[P, A, P, A, A]);
+    }
+
+    #[test]
+    fn test_duplicate_letter_counts() {
+        // Two L's and two E's against one of each: one L is yellow, and
+        // the green E uses up the only E.
+        check("allee", "angel", &[C, P, A, C, A]);
+        // A later green is used up before earlier yellows are assigned.
+        check("eerie", "there", &[P, A, P, A, C]);
+        check("lolly", "alloy", &[P, P, C, A, C]);
+        // Only as many yellows as there are copies in the answer.
+        check("speed", "abide", &[A, A, P, A, P]);
+    }
+
+    #[test]
+    fn test_success() {
+        check("prize", "prize", &[C, C, C, C, C]);
+    }
+
+    #[test]
+    fn test_decode_score() {
+        for (guess, answer) in [("spoon", "coats"), ("crane", "slate"), ("prize", "prize")] {
+            let score = score_wordle(guess.as_bytes(), answer.as_bytes());
+            assert_eq!(encode_score(decode_score(score, WORD_LEN).iter().cloned()), score);
+        }
+        assert_eq!(decode_score(score_wordle(b"spoon", b"coats"), WORD_LEN), [P, A, P, A, A]);
+        assert_eq!(render_score(score_wordle(b"spoon", b"coats"), WORD_LEN), "🟨⬛🟨⬛⬛");
+    }
+
+    #[test]
+    fn test_generic() {
+        let chars = |s: &str| s.chars().collect::<Vec<char>>();
+        let score = |cs: &[CharScore]| encode_score_wide(cs.iter().cloned());
+
+        // Matches score_wordle on bytes.
+        for (guess, answer) in [("spoon", "coats"), ("loose", "chore"), ("kazoo", "tools")] {
+            assert_eq!(
+                score_generic(guess.as_bytes(), answer.as_bytes()),
+                score_wordle(guess.as_bytes(), answer.as_bytes()) as u64
+            );
+        }
+        // Non-ASCII letters.
+        assert_eq!(score_generic(&chars("größe"), &chars("grüße")), score(&[C, C, A, C, C]));
+        assert_eq!(score_generic(&chars("ñandú"), &chars("dúñaa")), score(&[P, P, A, P, P]));
+        // Longer words, and other element types.
+        let guess = [1u32, 2, 3, 4, 5, 6, 7];
+        let answer = [7u32, 2, 3, 9, 9, 9, 9];
+        assert_eq!(score_generic(&guess, &answer), score(&[A, C, C, A, A, A, P]));
+    }
+
+    fn check_rules(rules: ScoringRules, guess: &str, answer: &str, score: &[CharScore]) {
+        assert_eq!(
+            score_with_rules(rules, guess.as_bytes(), answer.as_bytes()),
+            encode_score(score.iter().cloned())
+        );
+    }
+
+    #[test]
+    fn test_wildcard_answer() {
+        let w = ScoringRules::WildcardAnswer(b'*');
+        check_rules(w, "crane", "*rane", &[C, C, C, C, C]);
+        check_rules(w, "eeeee", "*abcd", &[C, A, A, A, A]);
+        // The wildcard is used up, so can't also make a letter present.
+        check_rules(w, "abcde", "edcb*", &[A, P, C, P, C]);
+        check_rules(w, "slate", "sl*te", &[C, C, C, C, C]);
+        check_rules(w, "xxxax", "a***b", &[A, C, C, C, A]);
+        // Under normal rules, it's just another character.
+        check_rules(ScoringRules::Standard, "crane", "*rane", &[A, C, C, C, C]);
+    }
+
+    #[test]
+    fn test_presence_rules() {
+        let reuse = ScoringRules::GreenImpliesYellowElsewhere;
+        let unlimited = ScoringRules::UnlimitedYellows;
+
+        // Two L's and E's against one of each, with a green E.
+        check_rules(ScoringRules::Standard, "allee", "angel", &[C, P, A, C, A]);
+        check_rules(reuse, "allee", "angel", &[C, P, A, C, P]);
+        check_rules(unlimited, "allee", "angel", &[C, P, P, C, P]);
+
+        // No greens, so reusing them makes no difference.
+        check_rules(ScoringRules::Standard, "spoon", "coats", &[P, A, P, A, A]);
+        check_rules(reuse, "spoon", "coats", &[P, A, P, A, A]);
+        check_rules(unlimited, "spoon", "coats", &[P, A, P, P, A]);
+
+        // All rules agree on the answer itself.
+        for rules in [ScoringRules::Standard, reuse, unlimited] {
+            check_rules(rules, "eerie", "eerie", &[C, C, C, C, C]);
+        }
+    }
+
+    #[test]
+    fn test_rules_by_name() {
+        for rules in [
+            ScoringRules::Standard,
+            ScoringRules::WildcardAnswer(b'?'),
+            ScoringRules::GreenImpliesYellowElsewhere,
+            ScoringRules::UnlimitedYellows,
+        ] {
+            assert_eq!(rules.to_string().parse::<ScoringRules>().unwrap(), rules);
+        }
+        assert!("liberal".parse::<ScoringRules>().is_err());
+        assert!("wildcard:".parse::<ScoringRules>().is_err());
+
+        // The score cache is built under the rules.
+        let guesses = vec![String::from("allee")];
+        let answers = vec![String::from("angel")];
+        let options = ScorerOptions {
+            rules: ScoringRules::UnlimitedYellows,
+            ..ScorerOptions::default()
+        };
+        let s = Scorer::from_word_lists_with_options(guesses, answers, &options).unwrap();
+        assert_eq!(s.score_cache[0][0], score_with_rules(ScoringRules::UnlimitedYellows, b"allee", b"angel"));
+        assert_ne!(s.score_cache[0][0], score_wordle(b"allee", b"angel"));
+    }
+
+    #[test]
+    fn test_from_word_lists() {
+        let guesses = vec![String::from("crane"), String::from("slate")];
+        let answers = vec![String::from("crate"), String::from("trace")];
+        let s = Scorer::from_word_lists(guesses, answers).unwrap();
+
+        // Answers get appended to the guesses.
+        assert_eq!(s.guesses, vec!["crane", "slate", "crate", "trace"]);
+        assert!(s.is_valid_guess("slate") && s.is_valid_guess("trace"));
+        assert!(!s.is_valid_guess("zzzzz"));
+        assert!(s.is_possible_answer("trace"));
+        assert!(!s.is_possible_answer("slate"));
+        for (g, guess) in s.guesses.iter().enumerate() {
+            for (a, answer) in s.answers.iter().enumerate() {
+                assert_eq!(s.score_cache[g][a], score_wordle(guess.as_bytes(), answer.as_bytes()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_score_rows() {
+        let s = small_scorer(&["zzzzb", "zzzbc"], &["aaaaa", "aaaab", "aaaac"]);
+        let rows = score_rows(&s.guesses, &s.answers).collect::<Vec<_>>();
+        assert_eq!(rows, s.score_cache);
+
+        let columns = score_columns(ScoringRules::Standard, &s.guesses, &s.answers, &[2, 0]).collect::<Vec<_>>();
+        let expected = rows.iter().map(|row| vec![row[2], row[0]]).collect::<Vec<_>>();
+        assert_eq!(columns, expected);
+
+        // Under other rules, the columns are scored by those rules.
+        let rules = ScoringRules::UnlimitedYellows;
+        let guesses = vec![String::from("eerie")];
+        let answers = vec![String::from("theme"), String::from("eerie")];
+        let columns = score_columns(rules, &guesses, &answers, &[0]).collect::<Vec<_>>();
+        assert_eq!(columns, vec![vec![score_with_rules(rules, b"eerie", b"theme")]]);
+        assert_ne!(columns[0][0], score_wordle(b"eerie", b"theme"));
+    }
+
+    #[test]
+    fn test_parse_combined() {
+        let strings = |v: &[&str]| v.iter().map(|s| String::from(*s)).collect::<Vec<_>>();
+
+        let (guesses, answers) = parse_combined("aahed\n*cigar\n\naalii\n*rebut\n", "test").unwrap();
+        assert_eq!(guesses, strings(&["aahed", "aalii"]));
+        assert_eq!(answers, strings(&["cigar", "rebut"]));
+
+        let text = "aahed\n[answers]\ncigar\nrebut\n[guesses]\naalii\n*sissy\n";
+        let (guesses, answers) = parse_combined(text, "test").unwrap();
+        assert_eq!(guesses, strings(&["aahed", "aalii"]));
+        assert_eq!(answers, strings(&["cigar", "rebut", "sissy"]));
+
+        assert!(matches!(
+            parse_combined("[solutions]\ncigar\n", "test"),
+            Err(WordleError::UnknownSection { .. })
+        ));
+        assert!(matches!(
+            parse_combined("*cigar\nsissies\n", "test"),
+            Err(WordleError::InconsistentWordLength { .. })
+        ));
+    }
+
+    #[test]
+    fn test_open_dictionary() {
+        let guesses = vec![String::from("crane"), String::from("slate")];
+        let answers = vec![String::from("crate"), String::from("crane")];
+        let options = ScorerOptions {
+            open_dictionary: true,
+            ..ScorerOptions::default()
+        };
+        let s = Scorer::from_word_lists_with_options(guesses, answers, &options).unwrap();
+
+        // Every distinct guess is an answer, and the cache covers them all.
+        assert_eq!(s.answers, vec!["crane", "slate", "crate"]);
+        assert_eq!(s.score_cache.len(), 4);
+        assert!(s.score_cache.iter().all(|row| row.len() == 3));
+    }
+
+    #[test]
+    fn test_word_list_stats() {
+        let s = small_scorer(&["crane", "crate", "crane"], &["crate", "trace"]);
+        assert_eq!(
+            s.word_list_stats,
+            WordListStats {
+                guesses: 3,
+                answers: 2,
+                overlap: 1,
+                distinct_guesses: 3,
+            }
+        );
+        assert_eq!(s.word_list_stats.to_string(), "3 guesses and 2 answers, 1 in both: 3 distinct guesses");
+    }
+
+    #[test]
+    fn test_missing_answers_added() {
+        let guesses = vec![String::from("crane"), String::from("crate")];
+        let answers = vec![String::from("crate"), String::from("trace")];
+        let options = ScorerOptions {
+            append_answers: false,
+            ..ScorerOptions::default()
+        };
+        let s = Scorer::from_word_lists_with_options(guesses, answers, &options).unwrap();
+
+        // "crate" was already there, so only "trace" gets added.
+        assert_eq!(s.guesses, vec!["crane", "crate", "trace"]);
+        assert_eq!(s.guess_greedily(1, &[0, 1], Ranking::WorstCase), 2);
+    }
+
+    #[test]
+    fn test_block_guesses() {
+        let mut s = small_scorer(&["zzzzb", "zzzbc", "zzzzd"], &["aaaaa", "aaaab", "aaaac"]);
+        let before = s.score(s.guess_index("zzzzd").unwrap(), 1);
+
+        // "aaaab" is an answer, so stays, and "qqqqq" isn't a guess.
+        let blocked = ["zzzbc", "aaaab", "qqqqq"].iter().map(|s| String::from(*s)).collect::<Vec<_>>();
+        assert_eq!(s.block_guesses(&blocked), vec![String::from("aaaab")]);
+        assert_eq!(s.guesses, vec!["zzzzb", "zzzzd", "aaaaa", "aaaab", "aaaac"]);
+        assert_eq!(s.guess_index("zzzbc"), None);
+        assert_eq!(s.guess_index("zzzzd"), Some(1));
+        assert_eq!(s.score(1, 1), before);
+    }
+
+    #[test]
+    fn test_optimise_guess_order_for() {
+        let guesses = ["zzzbc", "zzzzd"];
+        let answers = ["aaaaa", "aaaab", "aaaac", "aaaad"];
+
+        // Over all the answers, "zzzbc" leaves at most 2.
+        let mut s = small_scorer(&guesses, &answers);
+        s.optimise_guess_order(Ranking::WorstCase);
+        assert_eq!(s.guesses[0], "zzzbc");
+
+        // It doesn't split "aaaaa" from "aaaad", but "zzzzd" does.
+        let mut s = small_scorer(&guesses, &answers);
+        s.optimise_guess_order_for(Ranking::WorstCase, &[0, 3]);
+        assert_eq!(s.guesses[0], "zzzzd");
+        assert_eq!(s.answers.len(), 4);
+    }
+
+    #[test]
+    fn test_optimise_guess_order_keeps_scores() {
+        let guesses = ["zzzzz", "aback", "sissy", "crane", "eerie"];
+        let answers = ["cigar", "rebut", "sissy", "humph", "awake"];
+        for ranking in [Ranking::WorstCase, Ranking::ExpectedRemaining, Ranking::MaximizePartitions] {
+            let mut s = small_scorer(&guesses, &answers);
+            let before = s.guesses.clone();
+            s.optimise_guess_order(ranking);
+
+            // The order should really change, for the test to mean anything.
+            assert_ne!(s.guesses, before);
+            for (g, guess) in s.guesses.iter().enumerate() {
+                for (a, answer) in s.answers.iter().enumerate() {
+                    assert_eq!(s.score_cache[g][a], score_wordle(guess.as_bytes(), answer.as_bytes()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_load_guess_order() {
+        let mut s = small_scorer(&["zzzzb", "zzzbc"], &["aaaaa", "aaaab"]);
+        let path = std::env::temp_dir().join(format!("wordle-order-{}.txt", std::process::id()));
+
+        let expected = [3, 2, 0, 1].iter().map(|idx| s.score_cache[*idx].clone()).collect::<Vec<_>>();
+        std::fs::write(&path, "aaaab\naaaaa\nzzzzb\nzzzbc\n").unwrap();
+        s.load_guess_order(&path).unwrap();
+        assert_eq!(s.guesses, vec!["aaaab", "aaaaa", "zzzzb", "zzzbc"]);
+        assert_eq!(s.score_cache, expected);
+
+        // Not a permutation of the guesses.
+        std::fs::write(&path, "aaaab\naaaab\nzzzzb\nzzzbc\n").unwrap();
+        assert!(matches!(s.load_guess_order(&path), Err(WordleError::BadGuessOrder { .. })));
+        std::fs::write(&path, "aaaab\nzzzzb\nzzzbc\n").unwrap();
+        assert!(matches!(s.load_guess_order(&path), Err(WordleError::BadGuessOrder { .. })));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_write_score_matrix() {
+        let s = small_scorer(&["zzzzb"], &["aaaaa", "aaaab"]);
+        let path = std::env::temp_dir().join(format!("wordle-matrix-{}.bin", std::process::id()));
+        let words_path = PathBuf::from(format!("{}.words", path.display()));
+
+        s.write_score_matrix(&path).unwrap();
+        let data = std::fs::read(&path).unwrap();
+        assert_eq!(&data[..8], &[3, 0, 0, 0, 2, 0, 0, 0]);
+        assert_eq!(data.len(), 8 + 3 * 2);
+        assert_eq!(data[8 + 1] as BucketId, parse_score("aaaag", WORD_LEN).unwrap());
+        assert_eq!(data[8 + 2 * 2 + 1] as BucketId, s.score(2, 1));
+
+        // The sidecar reads back as the same words.
+        let (guesses, answers) = parse_combined(&std::fs::read_to_string(&words_path).unwrap(), "sidecar").unwrap();
+        assert_eq!((guesses, answers), (s.guesses.clone(), s.answers.clone()));
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&words_path).unwrap();
+    }
+
+    #[test]
+    fn test_filter_guess_length() {
+        let dir = std::env::temp_dir();
+        let guesses_path = dir.join(format!("wordle-mixed-guesses-{}.txt", std::process::id()));
+        let answers_path = dir.join(format!("wordle-mixed-answers-{}.txt", std::process::id()));
+        std::fs::write(&guesses_path, "crane\ncranes\na\nslate\n").unwrap();
+        std::fs::write(&answers_path, "crate\n").unwrap();
+
+        // Normally an error...
+        assert!(matches!(
+            Scorer::from_paths(&guesses_path, &answers_path),
+            Err(WordleError::InconsistentWordLength { .. })
+        ));
+        // ...but the odd words can be dropped instead.
+        let options = ScorerOptions {
+            filter_guess_length: true,
+            ..ScorerOptions::default()
+        };
+        let s = Scorer::from_paths_with_options(&guesses_path, &answers_path, &options).unwrap();
+        assert_eq!(s.guesses, vec!["crane", "slate", "crate"]);
+
+        std::fs::remove_file(&guesses_path).unwrap();
+        std::fs::remove_file(&answers_path).unwrap();
+    }
+
+    #[test]
+    fn test_error_trait() {
+        use std::error::Error;
+
+        let path = std::env::temp_dir().join("wordle-solver-does-not-exist.txt");
+        let err: Box<dyn Error> = Box::new(Scorer::from_paths(&path, &path).err().unwrap());
+        assert!(err.to_string().starts_with("Could not access"));
+        assert!(err.source().is_some());
+
+        let err = parse_score("gggxg", WORD_LEN).unwrap_err();
+        assert!(matches!(err, WordleError::InvalidInput(_)));
+        assert!(err.source().is_none());
+    }
+
+    #[test]
+    fn test_empty_word_lists() {
+        assert!(matches!(
+            Scorer::from_word_lists(vec![String::from("crane")], Vec::new()),
+            Err(WordleError::EmptyWordList { .. })
+        ));
+
+        // Metrics over no answers don't blow up.
+        let s = small_scorer(&[], &["crane"]);
+        for ranking in [Ranking::WorstCase, Ranking::ExpectedRemaining, Ranking::MaximizePartitions] {
+            assert_eq!(s.rank_guess(ranking, 0, &[]), (0.0, 0));
+            assert_eq!(s.best_guess(&[], ranking), None);
+        }
+        assert_eq!(s.entropy(0, &[]), 0.0);
+    }
+
+    #[test]
+    fn test_word_indices() {
+        // "aaaab" is both a guess and an answer, so is in the guesses
+        // twice.
+        let mut s = small_scorer(&["zzzzb", "aaaab"], &["aaaaa", "aaaab"]);
+
+        assert_eq!(s.guess_index("aaaab"), Some(1));
+        assert_eq!(s.guess_index("aaaaa"), Some(2));
+        assert_eq!(s.answer_index("aaaab"), Some(1));
+        assert_eq!(s.answer_index("zzzzb"), None);
+        assert_eq!(s.guess_index("qqqqq"), None);
+
+        // Lookups follow the guesses when they're reordered.
+        s.reorder_guesses(&[3, 2, 1, 0]);
+        assert_eq!(s.guess_index("aaaab"), Some(0));
+        assert_eq!(s.guess_index("zzzzb"), Some(3));
+    }
+
+    #[test]
+    fn test_read_candidates() {
+        let s = small_scorer(&["zzzzb"], &["aaaaa", "aaaab", "aaaac"]);
+        let path = std::env::temp_dir().join(format!("wordle-candidates-{}.txt", std::process::id()));
+
+        std::fs::write(&path, "aaaac\nAAAAA\naaaac\n").unwrap();
+        assert_eq!(s.read_candidates(&path).unwrap(), vec![0, 2]);
+        // Guesses aren't answers.
+        std::fs::write(&path, "aaaac\nzzzzb\n").unwrap();
+        assert!(matches!(s.read_candidates(&path), Err(WordleError::UnknownAnswer { .. })));
+        // Unless skipped, along with words of the wrong length.
+        std::fs::write(&path, "aaaac\nzzzzb\naaaaaa\naaaab\n").unwrap();
+        let (candidates, skipped) = s.read_candidates_skipping_unknown(&path).unwrap();
+        assert_eq!(candidates, vec![1, 2]);
+        assert_eq!(skipped, vec!["zzzzb", "aaaaaa"]);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(s.read_candidates_skipping_unknown(&path), Err(WordleError::Io { .. })));
+    }
+
+    #[test]
+    fn test_inconsistent_word_length() {
+        let guesses = vec![String::from("crane")];
+        let answers = vec![String::from("crate"), String::from("tram")];
+        match Scorer::from_word_lists(guesses, answers) {
+            Err(WordleError::InconsistentWordLength { word, source, expected }) => {
+                assert_eq!(word, "tram");
+                assert_eq!(source, "answers");
+                assert_eq!(expected, WORD_LEN);
+            }
+            _ => panic!("Expected an InconsistentWordLength error"),
+        }
+
+        // The answers set the length, which the guesses must match.
+        let guesses = vec![String::from("crane")];
+        let answers = vec![String::from("crates")];
+        assert!(matches!(
+            Scorer::from_word_lists(guesses, answers),
+            Err(WordleError::InconsistentWordLength { expected: 6, .. })
+        ));
+
+        // Scores for longer words don't fit a BucketId.
+        let answers = vec![String::from("abcdefghijk")];
+        match Scorer::from_word_lists(Vec::new(), answers) {
+            Err(e @ WordleError::UnsupportedWordLength { .. }) => assert_eq!(
+                e.to_string(),
+                "The words in answers are 11 letters long, but only words of 1 to 10 letters are supported"
+            ),
+            _ => panic!("Expected an UnsupportedWordLength error"),
+        }
+    }
+
+    #[test]
+    fn test_four_letter_words() {
+        let s = small_scorer(&["zzzb", "zzbc"], &["aaaa", "aaab", "aaac"]);
+        assert_eq!(s.word_len, 4);
+        assert_eq!(s.num_buckets(), 81);
+        assert_eq!(s.correct_score(), parse_score("gggg", 4).unwrap());
+        let guess = s.guess_index("zzzb").unwrap();
+        let answer = s.answers.iter().position(|a| a == "aaab").unwrap();
+        assert_eq!(s.score(guess, answer), parse_score("aaag", 4).unwrap());
+        assert_eq!(render_score(s.score(guess, answer), 4), "⬛⬛⬛🟩");
+        assert!(parse_score("aaaag", 4).is_err());
+
+        for answer in s.answers.iter() {
+            let mut state = GameState::new((0..s.answers.len()).collect());
+            state.play(&s, Ranking::WorstCase, &mut KnownAnswer::new(answer, s.rules)).unwrap();
+            assert_eq!(&s.guesses[state.history().last().unwrap().0], answer);
+        }
+    }
+
+    #[test]
+    fn test_six_letter_words() {
+        let s = small_scorer(&["zzzzbc"], &["aaaaaa", "aaaaab", "aaaaac", "aaaabc"]);
+        assert_eq!(s.word_len, 6);
+        assert_eq!(s.num_buckets(), 729);
+        // Too big for a byte.
+        assert_eq!(s.correct_score(), 364);
+        assert_eq!(s.correct_score(), parse_score("gggggg", 6).unwrap());
+        assert_eq!(parse_score("728", 6).unwrap(), parse_score("pppppp", 6).unwrap());
+        let guess = s.guess_index("zzzzbc").unwrap();
+        let answer = s.answers.iter().position(|a| a == "aaaabc").unwrap();
+        assert_eq!(s.score(guess, answer), parse_score("aaaagg", 6).unwrap());
+
+        // The guess tells all the answers apart.
+        let candidates = (0..s.answers.len()).collect::<Vec<_>>();
+        assert_eq!(s.bucket_count(guess, &candidates), 4);
+        for answer in s.answers.iter() {
+            let mut state = GameState::new(candidates.clone());
+            state.play(&s, Ranking::WorstCase, &mut KnownAnswer::new(answer, s.rules)).unwrap();
+            assert!(state.history().len() <= 2);
+            assert!(state.constraints(&s).allows(answer.as_bytes()));
+        }
+    }
+
+    #[test]
+    fn test_bucket_answers() {
+        let s = small_scorer(&["zzzzb"], &["aaaaa", "aaaab", "aaaac", "aaaad", "bbbbb", "zzzzb"]);
+        let input = [0, 1, 2, 3, 4, 5];
+        let buckets = s.bucket_answers(0, &input);
+
+        assert_eq!(buckets, vec![vec![0, 2, 3], vec![1, 4], vec![5]]);
+        // Largest first.
+        assert!(buckets.windows(2).all(|pair| pair[0].len() >= pair[1].len()));
+        // Every answer exactly once.
+        let mut all = buckets.concat();
+        all.sort_unstable();
+        assert_eq!(all, input);
+        // Within a bucket the scores match, and across buckets they don't.
+        let bucket_scores = buckets
+            .iter()
+            .map(|bucket| {
+                let score = s.score(0, bucket[0]);
+                assert!(bucket.iter().all(|a| s.score(0, *a) == score));
+                score
+            })
+            .collect::<HashSet<_>>();
+        assert_eq!(bucket_scores.len(), buckets.len());
+    }
+
+    #[test]
+    fn test_expected_remaining() {
+        let s = small_scorer(&["zzzzb", "zzzbc"], &["aaaaa", "aaaab", "aaaac"]);
+        let answers = [0, 1, 2];
+        // "zzzzb" can't tell "aaaaa" from "aaaac": buckets of size 2 and 1.
+        assert_eq!(s.find_greedy_worst_case(0, &answers), 2);
+        assert!((s.expected_remaining(0, &answers) - 5.0 / 3.0).abs() < 1e-9);
+        // "zzzbc" splits them all.
+        assert_eq!(s.find_greedy_worst_case(1, &answers), 1);
+        assert!((s.expected_remaining(1, &answers) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parse_score() {
+        assert_eq!(parse_score("gaYpc", WORD_LEN).unwrap(), encode_score([C, A, P, P, C].iter().cloned()));
+        assert_eq!(parse_score("AACPC", WORD_LEN).unwrap(), parse_score("aagyg", WORD_LEN).unwrap());
+        assert!(parse_score("gaay", WORD_LEN).is_err());
+        assert!(parse_score("gaayx", WORD_LEN).is_err());
+
+        // Encoded form.
+        assert_eq!(parse_score("121", WORD_LEN).unwrap(), correct_score(WORD_LEN));
+        assert_eq!(parse_score("0", WORD_LEN).unwrap(), parse_score("aaaaa", WORD_LEN).unwrap());
+        assert_eq!(parse_score("242", WORD_LEN).unwrap(), parse_score("ppppp", WORD_LEN).unwrap());
+        assert!(parse_score("243", WORD_LEN).is_err());
+        assert!(parse_score("99999999999999999999", WORD_LEN).is_err());
+    }
+
+    #[test]
+    fn test_parse_history() {
+        let history = parse_history("crane:aayaa  SALET:gaaya", WORD_LEN).unwrap();
+        assert_eq!(
+            history,
+            vec![
+                (String::from("crane"), parse_score("aayaa", WORD_LEN).unwrap()),
+                (String::from("salet"), parse_score("gaaya", WORD_LEN).unwrap()),
+            ]
+        );
+        assert!(parse_history("crane", WORD_LEN).is_err());
+    }
+
+    #[test]
+    fn test_best_guess() {
+        let s = small_scorer(&["zzzzb", "zzzbc"], &["aaaaa", "aaaab", "aaaac"]);
+
+        // Only "zzzbc" splits all three.
+        assert_eq!(s.best_guess(&[0, 1, 2], Ranking::WorstCase), Some(1));
+        // Last candidate standing should be guessed.
+        assert_eq!(s.best_guess(&[2], Ranking::WorstCase), Some(4));
+        assert_eq!(s.best_guess(&[], Ranking::WorstCase), None);
+
+        assert_eq!(s.best_guess(&[0, 1, 2], Ranking::MaximizePartitions), Some(1));
+        assert_eq!(s.bucket_count(0, &[0, 1, 2]), 2);
+        assert_eq!(s.bucket_count(1, &[0, 1, 2]), 3);
+
+        assert_eq!(s.guess_greedily(0, &[0, 1, 2], Ranking::WorstCase), 2);
+        assert_eq!(s.guess_greedily(0, &[0], Ranking::WorstCase), 1);
+        assert_eq!(s.greedy_guesses(0, &[0, 1, 2], Ranking::WorstCase), vec![1, 2]);
+
+        let candidates = s.apply_history(&parse_history("zzzbc:aaaaa", WORD_LEN).unwrap(), &[0, 1, 2]);
+        assert_eq!(candidates.unwrap(), vec![0]);
+        assert!(s.apply_history(&parse_history("qqqqq:aaaaa", WORD_LEN).unwrap(), &[0]).is_err());
+    }
+
+    #[test]
+    fn test_count_green_matches() {
+        let s = small_scorer(&["zzzzb"], &["aaaaa", "aaaab", "baaab", "abaaa"]);
+        let all = [0, 1, 2, 3];
+
+        assert_eq!(s.count_green_matches(&[], &all), 4);
+        assert_eq!(s.count_green_matches(&[Some(b'a')], &all), 3);
+        assert_eq!(s.count_green_matches(&[Some(b'a'), None, None, None, Some(b'b')], &all), 1);
+        assert_eq!(s.count_green_matches(&[None, None, None, None, Some(b'b')], &[0, 2]), 1);
+        assert_eq!(s.count_green_matches(&[Some(b'z')], &all), 0);
+    }
+
+    #[test]
+    fn test_filter_candidates_checked() {
+        let s = small_scorer(&["zzzzb"], &["aaaaa", "aaaab", "aaaac"]);
+
+        let score = s.score(0, 1);
+        assert_eq!(s.filter_candidates_checked(0, score, &[0, 1, 2], Some(1)).unwrap(), vec![1]);
+        // Tampered with, the score leaves other candidates, but not the
+        // target.
+        let tampered = parse_score("aaaaa", WORD_LEN).unwrap();
+        assert!(matches!(
+            s.filter_candidates_checked(0, tampered, &[0, 1, 2], Some(1)),
+            Err(WordleError::InconsistentScore { .. })
+        ));
+        assert_eq!(s.filter_candidates_checked(0, tampered, &[0, 1, 2], None).unwrap(), vec![0, 2]);
+        // And with the target unknown, something must be left.
+        assert!(matches!(
+            s.filter_candidates_checked(0, tampered, &[1], None),
+            Err(WordleError::NoCandidates { .. })
+        ));
+    }
+
+    #[test]
+    fn test_endgame() {
+        let mut s = small_scorer(&["fling", "march", "bents"], &["bight", "fight", "light", "might", "night", "right", "sight", "tight"]);
+        let all = (0..8).collect::<Vec<_>>();
+        let total = |s: &Scorer| all.iter().map(|t| s.guess_greedily(*t, &all, Ranking::WorstCase)).sum::<usize>();
+
+        // The exact endgame does as well as the optimal solver, and no
+        // worse than greedy.
+        let greedy = total(&s);
+        s.endgame_limit = 8;
+        let exact = total(&s);
+        assert_eq!(exact, OptimalSolver::new(&s).solve(&all).0);
+        assert!(exact <= greedy);
+    }
+
+    #[test]
+    fn test_expected_guesses() {
+        let s = small_scorer(&["zzzzb", "zzzbc"], &["aaaaa", "aaaab", "aaaac", "aaaad"]);
+        let all = [0, 1, 2, 3];
+
+        // Uniform weights give the average of guess_greedily.
+        let average = all.iter().map(|t| s.guess_greedily(*t, &all, Ranking::WorstCase)).sum::<usize>() as f64 / 4.0;
+        assert!((s.expected_guesses(&all, &[1.0; 4], Ranking::WorstCase) - average).abs() < 1e-9);
+
+        // All the weight on one answer gives its guess count.
+        let expected = s.expected_guesses(&all, &[0.0, 0.0, 0.0, 2.0], Ranking::WorstCase);
+        assert!((expected - s.guess_greedily(3, &all, Ranking::WorstCase) as f64).abs() < 1e-9);
+
+        // And in hard mode, where the opener's greens rule out the
+        // splitter "mpksi" that would otherwise come next.
+        let mut s = small_scorer(&["mpksi"], &["bills", "fills", "hills", "hsils", "mills", "pills"]);
+        let all = (0..s.answers.len()).collect::<Vec<_>>();
+        let weights = vec![1.0; all.len()];
+        let easy = s.expected_guesses(&all, &weights, Ranking::WorstCase);
+        s.hard_mode = true;
+        let total = all.iter().map(|t| s.guess_greedily(*t, &all, Ranking::WorstCase)).sum::<usize>();
+        let average = total as f64 / all.len() as f64;
+        assert!((s.expected_guesses(&all, &weights, Ranking::WorstCase) - average).abs() < 1e-9);
+        assert!(average > easy);
+    }
+
+    #[test]
+    fn test_guess_like_human() {
+        let s = small_scorer(&["zzzbc"], &["aabbb", "aaccc", "aaaad"]);
+
+        // 'a' is in all three, 'b', 'c' and 'd' in one each, so it's the
+        // first that wins the tie. The splitter "zzzbc" is never used.
+        assert_eq!(s.most_common_letters_candidate(&[0, 1, 2]), Some(1));
+        assert_eq!(s.most_common_letters_candidate(&[1, 2]), Some(2));
+        assert_eq!(s.most_common_letters_candidate(&[]), None);
+        assert_eq!(s.guess_like_human(0, &[0, 1, 2]), 1);
+        // "aabbb" can't tell the others apart, unlike "zzzbc".
+        assert_eq!(s.guess_like_human(2, &[0, 1, 2]), 3);
+    }
+
+    #[test]
+    fn test_anagram_clusters() {
+        let s = small_scorer(&[], &["stare", "cigar", "tears", "rebut", "aster", "tuber"]);
+        assert_eq!(s.anagram_clusters(), vec![vec![0, 2, 4], vec![3, 5]]);
+    }
+
+    #[test]
+    fn test_common_letter_guesses() {
+        let s = small_scorer(&["abcde", "abcdd", "fghij"], &["abcde", "abcdf", "abceg"]);
+
+        // a, b and c are in all three answers, and d and e in two, so
+        // the top five rule out f and g, and repeated letters.
+        assert_eq!(s.common_letter_guesses(&[0, 1, 2], 5), vec![0, 3]);
+        assert_eq!(s.common_letter_guesses(&[0, 1, 2], 4), Vec::<usize>::new());
+        assert_eq!(s.common_letter_guesses(&[1], 5), vec![4]);
+    }
+
+    #[test]
+    fn test_cached_guesses() {
+        let guesses = ["zzzzb", "zzzbc", "zzbcd", "zzzzz"].iter().map(|s| String::from(*s)).collect::<Vec<_>>();
+        let answers = ["aaaaa", "aaaab", "aaaac", "aaaad"].iter().map(|s| String::from(*s)).collect::<Vec<_>>();
+        let full = Scorer::from_word_lists(guesses.clone(), answers.clone()).unwrap();
+        let options = ScorerOptions {
+            cached_guesses: Some(1),
+            ..ScorerOptions::default()
+        };
+        let sparse = Scorer::from_word_lists_with_options(guesses, answers, &options).unwrap();
+
+        // Only "zzbcd", the best, and the answers are cached.
+        let cached = sparse.score_cache.iter().map(|row| !row.is_empty()).collect::<Vec<_>>();
+        assert_eq!(cached, vec![false, false, true, false, true, true, true, true]);
+
+        let all = [0, 1, 2, 3];
+        for guess in 0..full.guesses.len() {
+            assert_eq!(sparse.scores(guess), full.scores(guess));
+            for answer in all.iter() {
+                assert_eq!(sparse.score(guess, *answer), full.score(guess, *answer));
+            }
+        }
+        assert_eq!(
+            sparse.solve_all(&all, Ranking::WorstCase, false, None),
+            full.solve_all(&all, Ranking::WorstCase, false, None)
+        );
+    }
+
+    #[test]
+    fn test_random_opener() {
+        let s = small_scorer(&["zzzzb", "zzzbc"], &["aaaaa", "aaaab", "aaaac"]);
+
+        // The top quarter of the five guesses is "zzzbc" and the first
+        // of those that tie behind it.
+        let openers = (0..20)
+            .map(|seed| s.random_opener(&[0, 1, 2], Ranking::WorstCase, &mut Rng::new(seed)).unwrap())
+            .collect::<HashSet<_>>();
+        assert_eq!(openers, [0, 1].iter().copied().collect());
+    }
+
+    #[test]
+    fn test_answers_by_difficulty() {
+        let s = small_scorer(&["zzzzb"], &["aaaaa", "aaaab", "aaaac"]);
+        // "zzzzb" leaves "aaaaa" and "aaaac" together.
+        assert_eq!(s.answers_by_difficulty(0, &[1, 2, 0]), vec![0, 2, 1]);
+    }
+
+    #[test]
+    fn test_second_guesses() {
+        let s = small_scorer(&["zzzzb", "zzzbc"], &["aaaaa", "aaaab", "aaaac"]);
+
+        // "zzzzb" picks out "aaaab", and leaves the other two to split,
+        // which guessing one of them does.
+        let second = s.second_guesses(0, Ranking::WorstCase);
+        assert_eq!(second.len(), 2);
+        assert_eq!(second[&parse_score("aaaag", WORD_LEN).unwrap()], 3);
+        assert_eq!(second[&parse_score("aaaaa", WORD_LEN).unwrap()], 2);
+
+        // Opening with "aaaab", its own bucket is left out.
+        let second = s.second_guesses(3, Ranking::WorstCase);
+        assert_eq!(second.keys().copied().collect::<Vec<_>>(), vec![parse_score("gggga", WORD_LEN).unwrap()]);
+
+        // In hard mode, the greens from "bills" rule out "mpksi".
+        let mut s = small_scorer(&["mpksi"], &["bills", "fills", "hills", "mills", "pills"]);
+        let bills = s.guess_index("bills").unwrap();
+        let ills = parse_score("agggg", WORD_LEN).unwrap();
+        assert_eq!(s.guesses[s.second_guesses(bills, Ranking::WorstCase)[&ills]], "mpksi");
+        s.hard_mode = true;
+        assert_ne!(s.guesses[s.second_guesses(bills, Ranking::WorstCase)[&ills]], "mpksi");
+    }
+
+    #[test]
+    fn test_top_guesses() {
+        let s = small_scorer(&["zzzzb", "zzzbc"], &["aaaaa", "aaaab", "aaaac"]);
+
+        // "zzzbc" tells them all apart. Everything else leaves a pair,
+        // and ties stay in guess order.
+        let top = s.top_guesses(&[0, 1, 2], 3);
+        let words = top.iter().map(|e| e.word.as_str()).collect::<Vec<_>>();
+        assert_eq!(words, vec!["zzzbc", "zzzzb", "aaaaa"]);
+        assert_eq!((top[0].worst_case, top[0].num_buckets), (1, 3));
+        assert_eq!((top[1].worst_case, top[1].num_buckets), (2, 2));
+        assert!((top[1].expected_remaining - 5.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_solve_all() {
+        let s = small_scorer(&["zzzzb", "zzzbc"], &["aaaaa", "aaaab", "aaaac"]);
+
+        // "zzzbc" tells them all apart.
+        let results = s.solve_all(&[0, 1, 2], Ranking::WorstCase, false, None);
+        let guesses = results.iter().map(|r| r.guesses.clone()).collect::<Vec<_>>();
+        assert_eq!(guesses, vec![vec![1, 2], vec![1, 3], vec![1, 4]]);
+
+        // Opening with an answer solves it at once, but can't tell the
+        // other two apart.
+        let results = s.solve_all(&[0, 1, 2], Ranking::WorstCase, false, Some(3));
+        assert_eq!(results.iter().map(|r| r.num_guesses()).collect::<Vec<_>>(), vec![2, 1, 3]);
+        assert_eq!(results[2].answer, 2);
+
+        // Always playing "zzzzb" then "zzzbc", unless "zzzzb" is right.
+        let results = s.solve_all_with_openers(&[0, 1, 2], Ranking::WorstCase, false, &[0, 1], 0);
+        let guesses = results.iter().map(|r| r.guesses.clone()).collect::<Vec<_>>();
+        assert_eq!(guesses, vec![vec![0, 1, 2], vec![0, 1, 3], vec![0, 1, 4]]);
+        let results = s.solve_all_with_openers(&[0, 1, 2], Ranking::WorstCase, false, &[3, 1], 0);
+        assert_eq!(results.iter().map(|r| r.num_guesses()).collect::<Vec<_>>(), vec![3, 1, 3]);
+    }
+
+    #[test]
+    fn test_explain_guess() {
+        let s = small_scorer(&["zzzzb", "zzzbc"], &["aaaaa", "aaaab", "aaaac"]);
+        assert_eq!(s.explain_guess(0, &[0, 1, 2]), "splits 3 candidates into 2 groups; worst case 2 remain");
+        assert_eq!(s.explain_guess(1, &[0, 1, 2]), "splits 3 candidates into 3 groups; the answer is then known");
+    }
+
+    #[test]
+    fn test_is_feedback_possible() {
+        let s = small_scorer(&["zzzbc"], &["aaaaa", "aaaab", "aaaac"]);
+        assert!(s.is_feedback_possible("zzzbc", parse_score("aaaya", WORD_LEN).unwrap()));
+        assert!(s.is_feedback_possible("zzzbc", parse_score("aaaaa", WORD_LEN).unwrap()));
+        // No answer has a 'z'.
+        assert!(!s.is_feedback_possible("zzzbc", parse_score("gaaaa", WORD_LEN).unwrap()));
+        // Guesses needn't be in the list.
+        assert!(s.is_feedback_possible("baaaa", parse_score("ygggy", WORD_LEN).unwrap()));
+        // Four greens and a yellow can't happen.
+        assert!(!s.is_feedback_possible("aaaab", parse_score("ggggy", WORD_LEN).unwrap()));
+        assert!(!s.is_feedback_possible("aaaabc", parse_score("ggggg", WORD_LEN).unwrap()));
+    }
+
+    #[test]
+    fn test_residual_worst_case() {
+        let s = small_scorer(&["zzzzb", "zzzbc"], &["aaaaa", "aaaab", "aaaac", "aaaad"]);
+
+        // "zzzzb" leaves "aaaaa", "aaaac" and "aaaad", and guessing any of
+        // them leaves the other two. "zzzbc" leaves "aaaaa" and "aaaad",
+        // and guessing one leaves the other.
+        assert_eq!(s.residual_worst_case(0, &[0, 1, 2, 3]), 2);
+        assert_eq!(s.residual_worst_case(1, &[0, 1, 2, 3]), 1);
+        assert_eq!(s.residual_worst_case(1, &[1, 2]), 0);
+        assert_eq!(s.rank_guess(Ranking::ResidualWorstCase, 1, &[0, 1, 2, 3]), (1.0, 2));
+        assert_eq!(s.best_guess(&[0, 1, 2, 3], Ranking::ResidualWorstCase), Some(1));
+        assert_eq!("residual-worst-case".parse::<Ranking>().unwrap(), Ranking::ResidualWorstCase);
+    }
+
+    #[test]
+    fn test_best_guesses() {
+        let s = small_scorer(&["zzzzb", "zzzzc", "zzzzz"], &["aaaaa", "aaaab", "aaaac"]);
+
+        // Everything but "zzzzz" splits the answers 2/1.
+        assert_eq!(s.best_guesses(&[0, 1, 2], Ranking::WorstCase), vec![0, 1, 3, 4, 5]);
+        // best_guess prefers the first one that could be the answer.
+        assert_eq!(s.best_guess(&[0, 1, 2], Ranking::WorstCase), Some(3));
+        assert_eq!(s.best_guesses(&[1], Ranking::WorstCase), vec![4]);
+
+        // With those excluded, "zzzzz" is all that's left.
+        assert_eq!(s.best_guess_excluding(&[0, 1, 2], Ranking::WorstCase, &[0, 1, 3, 4, 5]), Some(2));
+        assert_eq!(s.best_guess_excluding(&[0, 1, 2], Ranking::WorstCase, &[0, 1, 2, 3, 4, 5]), None);
+    }
+
+    #[test]
+    fn test_score_row_vs_guesses() {
+        let mut s = small_scorer(&["zzzzb", "zzzbc"], &["aaaaa", "aaaab"]);
+
+        let row = s.score_row_vs_guesses(1);
+        let expected = ["gggya", "ggggg", "aaaaa", "aaaya"]
+            .iter()
+            .map(|score| parse_score(score, WORD_LEN).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(row, expected);
+        assert!(s.guess_rows.lock().unwrap().is_empty());
+
+        s.cache_guess_rows = true;
+        assert_eq!(s.score_row_vs_guesses(1), expected);
+        assert_eq!(s.score_row_vs_guesses(1), expected);
+        assert_eq!(s.guess_rows.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_answer_endgame() {
+        let mut s = small_scorer(&["zzbcd"], &["aaaaa", "aaaab", "aaaac", "aaaad", "aabbc"]);
+        let rankings = [Ranking::WorstCase, Ranking::ExpectedRemaining, Ranking::MaximizePartitions];
+        let answer_guesses = (0..s.answers.len()).map(|a| s.guess_index(&s.answers[a]).unwrap()).collect::<Vec<_>>();
+
+        // Scored from the answer matrix, the guess picked is as good as
+        // the best answer scored from the full cache.
+        s.use_answer_endgame(10);
+        for candidates in [vec![0, 1, 2, 3, 4], vec![0, 1, 2], vec![1, 3]].iter() {
+            for ranking in rankings.iter() {
+                let best = answer_guesses
+                    .iter()
+                    .map(|guess| s.rank_guess(*ranking, *guess, candidates))
+                    .min_by(|a, b| a.partial_cmp(b).unwrap())
+                    .unwrap();
+                let guess = s.best_guess(candidates, *ranking).unwrap();
+                assert!(answer_guesses.contains(&guess));
+                assert_eq!(s.rank_guess(*ranking, guess, candidates), best);
+            }
+        }
+
+        // "zzbcd" is better than any answer, when it's allowed.
+        s.use_answer_endgame(0);
+        assert_eq!(s.best_guess(&[0, 1, 2, 3, 4], Ranking::WorstCase), Some(0));
+    }
+
+    #[test]
+    fn test_prefer_input_order() {
+        let mut s = small_scorer(&["zzzbc", "zzzcb"], &["aaaab", "aaaac", "aaaad"]);
+
+        // Both given guesses tell the answers apart, and no answer does.
+        // Once the search order puts "zzzcb" first, it wins the tie,
+        // unless the input order is preferred.
+        s.reorder_guesses(&[1, 0, 2, 3, 4]);
+        assert_eq!(s.guesses[s.best_guess(&[0, 1, 2], Ranking::WorstCase).unwrap()], "zzzcb");
+        s.prefer_input_order = true;
+        assert_eq!(s.best_guesses(&[0, 1, 2], Ranking::WorstCase), vec![1, 0]);
+        assert_eq!(s.guesses[s.best_guess(&[0, 1, 2], Ranking::WorstCase).unwrap()], "zzzbc");
+    }
+
+    #[test]
+    fn test_candidate_view() {
+        let s = small_scorer(&["zzzzb", "zzzbc"], &["aaaaa", "aaaab", "aaaac"]);
+
+        let view = s.clone_for_candidates(&[0, 2]);
+        assert_eq!(view.best_guess(Ranking::WorstCase), s.best_guess(&[0, 2], Ranking::WorstCase));
+        assert_eq!(view.bucket_answers(0), vec![vec![0, 2]]);
+
+        let narrowed = view.narrow(1, parse_score("aaaag", WORD_LEN).unwrap());
+        assert_eq!(narrowed.candidates(), &[2]);
+        assert_eq!(narrowed.best_guess(Ranking::WorstCase), Some(4));
+    }
+
+    #[test]
+    fn test_strategy_within() {
+        let answers = ["aaaaa", "aaaab", "aaaac"];
+
+        // Nothing distinguishes all three answers in one guess.
+        let s = small_scorer(&["zzzzb"], &answers);
+        assert_eq!(s.exists_strategy_within(1), None);
+        assert_eq!(s.exists_strategy_within(2), None);
+        assert!(s.exists_strategy_within(3).is_some());
+
+        // "zzzbc" does.
+        let s = small_scorer(&["zzzbc"], &answers);
+        assert_eq!(s.exists_strategy_within(2), Some(0));
+    }
+
+    #[test]
+    fn test_hard_mode() {
+        let answers = ["sheep", "eerie", "there", "geese", "speed", "abide", "elder", "cheap", "tepee"];
+        let mut s = small_scorer(&["route"], &answers);
+        let idx = |s: &Scorer, w: &str| s.guess_index(w).unwrap();
+
+        // "eerie" against "there" needs two E's, one at the end.
+        let history = [(idx(&s, "eerie"), parse_score("yayag", WORD_LEN).unwrap())];
+        assert!(s.is_hard_mode_legal(&history, idx(&s, "there")));
+        assert!(!s.is_hard_mode_legal(&history, idx(&s, "route")));
+        assert!(!s.is_hard_mode_legal(&history, idx(&s, "cheap")));
+        let excluded = s.hard_mode_excluded(&history);
+        for word in ["eerie", "route", "cheap"].iter() {
+            assert!(excluded.contains(&idx(&s, word)));
+        }
+        assert!(!excluded.contains(&idx(&s, "there")));
+
+        // Every guess made in hard mode respects the feedback before it.
+        s.hard_mode = true;
+        let all = (0..s.answers.len()).collect::<Vec<_>>();
+        for result in s.solve_all_with_progress(&all, Ranking::WorstCase, false, Some(idx(&s, "route")), 0) {
+            let history = result.guesses.iter().map(|g| (*g, s.score(*g, result.answer))).collect::<Vec<_>>();
+            for turn in 1..history.len() {
+                assert!(s.is_hard_mode_legal(&history[..turn], history[turn].0));
+            }
+            assert_eq!(s.guesses[*result.guesses.last().unwrap()], s.answers[result.answer]);
+        }
+    }
+
+    #[test]
+    fn test_solve_optimally() {
+        let s = small_scorer(&["zzzzb"], &["aaaaa", "aaaab", "aaaac"]);
+        let all = [0, 1, 2];
+        let worst_case = |answers: &[usize]| s.solve_optimally(answers, 6, None).0.map(|m| m.worst_case);
+
+        // The one-candidate and guess-is-answer cases match greedy.
+        assert_eq!(worst_case(&[1]), Some(1));
+        assert_eq!(worst_case(&[0, 2]), Some(2));
+        // One of a pair is always left after the first guess: 2 + 2 + 3
+        // guesses opening with "zzzzb".
+        let (solution, proven) = s.solve_optimally(&all, 6, None);
+        let solution = solution.unwrap();
+        assert!(proven);
+        assert_eq!(solution.worst_case, 3);
+        assert_eq!(solution.average, 7.0 / 3.0);
+        assert_eq!(s.strategy_within(&all, 3), Some(solution.first_guess));
+        let greedy_worst = all.iter().map(|t| s.guess_greedily(*t, &all, Ranking::WorstCase)).max();
+        assert_eq!(greedy_worst, Some(3));
+
+        // Capped below that, there's no solution.
+        assert_eq!(s.solve_optimally(&all, 2, None), (None, true));
+        // And with no budget, there's no proof either way.
+        assert_eq!(s.solve_optimally(&all, 6, Some(0)), (None, false));
+    }
+
+    #[test]
+    fn test_singleton_count() {
+        let s = small_scorer(&["zzzzb"], &["aaaaa", "aaaab", "aaaac"]);
+        assert_eq!(s.singleton_count(0, &[0, 1, 2]), 1);
+        // Guessing "aaaab" itself doesn't count.
+        assert_eq!(s.singleton_count(2, &[0, 1, 2]), 0);
+    }
+
+    #[test]
+    fn test_entropy() {
+        let s = small_scorer(&["zzzzb", "zzzbc"], &["aaaaa", "aaaab", "aaaac", "aaaad"]);
+        let answers = [0, 1, 2, 3];
+
+        // Splits 3/1.
+        let expected = -(0.75f64 * 0.75f64.log2() + 0.25 * 0.25f64.log2());
+        assert!((s.entropy(0, &answers) - expected).abs() < 1e-9);
+        // Splits 2/1/1.
+        assert!((s.entropy(1, &answers) - 1.5).abs() < 1e-9);
+        // Guessing any of the answers only splits 3/1.
+        assert_eq!(s.find_max_entropy(&answers), Some((1, 1.5)));
+    }
+
+    #[test]
+    fn test_entropies() {
+        let s = small_scorer(&["zzzzb", "zzzbc"], &["aaaaa", "aaaab", "aaaac", "aaaad"]);
+
+        let all = [0, 1, 2, 3];
+        let entropies = s.entropies(&all);
+        assert_eq!(entropies.len(), s.guesses.len());
+        for (guess, entropy) in entropies.iter().enumerate() {
+            assert_eq!(*entropy, s.entropy(guess, &all));
+        }
+        assert_eq!(s.find_max_entropy(&all), Some((1, entropies[1])));
+    }
+
+    #[test]
+    fn test_best_opener_pair() {
+        let s = small_scorer(&["zzzzb", "zzzzc", "zzzzz"], &["aaaaa", "aaaab", "aaaac"]);
+
+        // Together, "zzzzb" and "zzzzc" tell all the answers apart.
+        let answers = [0, 1, 2];
+        let (first, second, expected) = s.best_opener_pair(&answers, 2).unwrap();
+        assert_eq!(expected, 1.0);
+        let mut pair = [first, second];
+        pair.sort();
+        assert_eq!(pair, ["zzzzb", "zzzzc"]);
+
+        // Against fewer answers, one guess can tell them apart.
+        let idx = |w: &str| s.answers.iter().position(|a| a == w).unwrap();
+        let (_, _, expected) = s.best_opener_pair(&[idx("aaaaa"), idx("aaaab")], 2).unwrap();
+        assert_eq!(expected, 1.0);
+
+        // Without a shortlist, there's no pair to try.
+        assert!(s.best_opener_pair(&answers, 0).is_none());
+    }
+
+    #[test]
+    fn test_partition_by_guesses() {
+        let s = small_scorer(&["zzzzb", "zzzzc"], &["aaaaa", "aaaab", "aaaac"]);
+
+        let mut partition = s.partition_by_guesses(&[0], &[0, 1, 2]);
+        partition.sort();
+        assert_eq!(partition, vec![vec![0, 2], vec![1]]);
+        let mut partition = s.partition_by_guesses(&[0, 1], &[0, 1, 2]);
+        partition.sort();
+        assert_eq!(partition, vec![vec![0], vec![1], vec![2]]);
+    }
+
+    #[test]
+    fn test_answer_only_indistinguishable_pairs() {
+        let s = small_scorer(&["zzzbc"], &["aaaaa", "aaaab", "aaaac"]);
+        // Only "zzzbc", which isn't an answer, or guessing one of them
+        // tells any two of these apart.
+        assert_eq!(s.answer_only_indistinguishable_pairs(), vec![(0, 1), (0, 2), (1, 2)]);
+
+        // "aabcd" tells the others apart, and "aaaaa" tells it from them.
+        let s = small_scorer(&["zzzbc"], &["aaaaa", "aaaab", "aaaac", "aabcd"]);
+        assert_eq!(s.answer_only_indistinguishable_pairs(), vec![]);
+    }
+
+    #[test]
+    fn test_separating_set_greedy() {
+        let s = small_scorer(&["zzzzb", "zzzzc", "zzzbc"], &["aaaaa", "aaaab", "aaaac"]);
+
+        // "zzzbc" does it alone.
+        assert_eq!(s.separating_set_greedy(), vec![2]);
+
+        // With "aaaad" as well, "zzzbc" leaves it with "aaaaa", and the
+        // answers themselves are needed to split those.
+        let s = small_scorer(&["zzzbc"], &["aaaaa", "aaaab", "aaaac", "aaaad"]);
+        let set = s.separating_set_greedy();
+        assert_eq!(set.len(), 2);
+        assert_eq!(set[0], 0);
+        assert_eq!(s.partition_by_guesses(&set, &[0, 1, 2, 3]).len(), 4);
+    }
+
+    #[test]
+    fn test_pattern() {
+        let english = Alphabet::english();
+        let p = Pattern::parse(".IGHT", &english).unwrap();
+        assert!(p.matches(b"light"));
+        assert!(!p.matches(b"lithe"));
+        // Patterns of other lengths are for other word lengths.
+        let p = Pattern::parse("ight", &english).unwrap();
+        assert_eq!(p.word_len(), 4);
+        assert!(!p.matches(b"light"));
+        assert!(Pattern::parse("", &english).is_err());
+        assert!(Pattern::parse(".ig?t", &english).is_err());
+    }
+
+    #[test]
+    fn test_pattern_classes() {
+        let english = Alphabet::english();
+        let p = Pattern::parse("[aeiou]..[^xyz].", &english).unwrap();
+        assert!(p.matches(b"about"));
+        assert!(p.matches(b"irate"));
+        assert!(!p.matches(b"crane"));
+        assert!(!p.matches(b"epoxy"));
+        assert!(Pattern::parse("[ae..", &english).is_err());
+        assert!(Pattern::parse("[a1]....", &english).is_err());
+        // A class is one position.
+        assert_eq!(Pattern::parse("[ae]...", &english).unwrap().word_len(), 4);
+    }
+
+    #[test]
+    fn test_pattern_alphabet() {
+        // Letters come from the words, so can be outside a-z.
+        let alphabet = Alphabet::from_words(["ab1cd", "ab2cd", "abecd"].iter().copied());
+        let p = Pattern::parse("..[^1]..", &alphabet).unwrap();
+        assert!(!p.matches(b"ab1cd"));
+        assert!(p.matches(b"ab2cd"));
+        assert!(p.matches(b"abecd"));
+        assert!(Pattern::parse("..[12]..", &alphabet).unwrap().matches(b"ab2cd"));
+        // Letters the words don't use are allowed, but match nothing.
+        assert!(!Pattern::parse("..[z2]..", &alphabet).unwrap().matches(b"abzcd"));
+        assert!(Pattern::parse("..3..", &alphabet).is_err());
+    }
+}
@@ -2,344 +2,984 @@
 // Wordle solver
 //
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
-use std::collections::HashMap;
+use std::collections::BTreeMap;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
 use std::process;
 
-const WORD_LEN: usize = 5;
+mod interactive;
+
+use wordle_solver::{
+    can_solve_noisy, decode_score, parse_history, parse_score, practice, render_score,
+    score_with_rules, serve, words_dir, Adversary, BucketId, GameState, KnownAnswer, NoisyOracle, OptimalSolver,
+    Oracle, Pattern, Ranking, Rng, Scorer, ScorerOptions, ScoringRules, SolveResult, WordleError, MAX_WORD_LEN,
+};
+
 const DEPTH: usize = 4;
 
-const MAX_BUCKET: usize = 3 * 3 * 3 * 3 * 3;
+// Guesses allowed in a real game.
+const MAX_GUESSES: usize = 6;
 
-// Bucket can be stored as u8 - 3^5 <= 255.
-type BucketId = u8;
+// Feedbacks listed on the --cheat-sheet, to fit on a page.
+const CHEAT_SHEET_ROWS: usize = 20;
+
+// Exit statuses. Results go to stdout and diagnostics to stderr, and
+// the status says which kind of outcome it was:
+//
+//  * 0: success, with the result on stdout.
+//  * 1: the question was answered, but negatively, e.g. no candidates
+//       remain, or no strategy exists.
+//  * 2: an error, such as bad input or an unreadable word list, with a
+//       message on stderr. clap also uses 2 for bad arguments.
+const EXIT_NEGATIVE: i32 = 1;
+const EXIT_ERROR: i32 = 2;
 
 ////////////////////////////////////////////////////////////////////////
-// Core scoring/classification algorithm
+// Modes of operation
 //
 
-// Result of a guessed letter, as determined by Wordle
-#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
-enum CharScore {
-    Absent,
-    Correct,
-    Present,
+// The seed to use for something that should vary between sessions: the
+// one given, or else one from the time, logged so the session can be
+// repeated with --seed.
+fn session_seed(seed: Option<u64>) -> u64 {
+    seed.unwrap_or_else(|| {
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+        let seed = now.as_nanos() as u64;
+        eprintln!("Seed: {}", seed);
+        seed
+    })
 }
 
-// Compactly encode an arry of CharScores. Assumes the word isn't too long.
-fn encode_score(cs: impl Iterator<Item = CharScore>) -> u8 {
-    cs.map(|c| c as u8).fold(0, |acc, c| acc * 3 + c)
+// Join words for printing, showing no more than 'max' of them.
+fn list_words(words: &[&str], separator: &str, max: usize) -> String {
+    let shown = words.iter().take(max).copied().collect::<Vec<_>>().join(separator);
+    if words.len() > max {
+        format!("{} ... and {} more", shown, words.len() - max)
+    } else {
+        shown
+    }
 }
 
-// Return the score for a guess against a specific actual answer, encoded.
-fn score_wordle(guess: &[u8], answer: &[u8]) -> u8 {
-    assert_eq!(guess.len(), WORD_LEN);
-    assert_eq!(answer.len(), WORD_LEN);
-
-    let mut corrects = [false; WORD_LEN];
-    let mut used = [false; WORD_LEN];
-    for idx in 0..guess.len() {
-        if guess[idx] == answer[idx] {
-            corrects[idx] = true;
-            // Correctly guessed letters are "used up".
-            used[idx] = true;
+// Show how the best 'top' guesses, once ordered, split the answers, as
+// the number of buckets of each size, largest first, to show why they
+// rank as they do.
+fn trace_optimise(s: &Scorer, top: usize) {
+    let answer_idxs = (0..s.answers.len()).collect::<Vec<_>>();
+    for guess in 0..top.min(s.guesses.len()) {
+        let mut histogram = BTreeMap::new();
+        for bucket in s.bucket_answers(guess, &answer_idxs) {
+            *histogram.entry(bucket.len()).or_insert(0) += 1;
         }
+        let sizes = histogram
+            .iter()
+            .rev()
+            .map(|(size, count)| format!("{}x{}", size, count))
+            .collect::<Vec<_>>();
+        println!("{}. {}: bucket sizes {}", guess + 1, s.guesses[guess], sizes.join(" "));
     }
+}
 
-    // Look for the presence of a character in the answer that isn't used,
-    // and if it's present use it up and return true. Otherwise false.
-    fn check_presence(c: u8, answer: &[u8], used: &mut [bool]) -> bool {
-        for (idx, d) in answer.iter().enumerate() {
-            if !used[idx] && c == *d {
-                used[idx] = true;
-                return true;
+// Exhaustively search for a way to always solve in DEPTH guesses.
+// If 'metric_idxs' is given, the search order is optimised for those
+// answers alone.
+fn search(s: &mut Scorer, opts: &Opts, answer_idxs: &[usize], metric_idxs: Option<&[usize]>) {
+    assert!(opts.shard_index < opts.shard_count);
+    match &opts.load_order {
+        Some(path) => s.load_guess_order(path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(EXIT_ERROR);
+        }),
+        None => {
+            let log = match metric_idxs {
+                Some(metric_idxs) => s.optimise_guess_order_for(opts.ranking(), metric_idxs),
+                None => s.optimise_guess_order(opts.ranking()),
+            };
+            // The ranks are progress, not results, so go to stderr.
+            let stderr = std::io::stderr();
+            let mut stderr = BufWriter::new(stderr.lock());
+            for line in log.iter() {
+                let _ = writeln!(stderr, "{}", line);
             }
+            drop(stderr);
+            trace_optimise(s, opts.trace_optimise);
+        }
+    }
+    if let Some(path) = &opts.dump_order {
+        if let Err(e) = s.write_guess_order(path) {
+            eprintln!("{}", e);
+            process::exit(EXIT_ERROR);
         }
-        false
     }
 
-    encode_score(corrects.iter().zip(guess.iter()).map(|(is_correct, c)| {
-        if *is_correct {
-            CharScore::Correct
-        } else if check_presence(*c, answer, &mut used) {
-            CharScore::Present
-        } else {
-            CharScore::Absent
+    let possible = can_solve_noisy(s, DEPTH, answer_idxs, opts.shard_index, opts.shard_count);
+    if possible {
+        println!("Success with {} guesses!", DEPTH);
+    } else {
+        println!("Cannot fully determine with {} guesses. Oh well.", DEPTH);
+        process::exit(EXIT_NEGATIVE);
+    }
+}
+
+// Print the best next guess, and nothing else, for scripting. Exits
+// with status 1 if no candidates remain, and 2 on bad input.
+fn next(s: &Scorer, opts: &Opts, answer_idxs: &[usize], history: &str) {
+    let history = parse_history(history, s.word_len).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(EXIT_ERROR);
+    });
+    let candidates = s.apply_history(&history, answer_idxs).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(EXIT_ERROR);
+    });
+
+    let best = if s.hard_mode {
+        // apply_history has checked the guesses are known.
+        let history = history
+            .iter()
+            .map(|(word, score)| (s.guess_index(word).unwrap(), *score))
+            .collect::<Vec<_>>();
+        s.best_hard_mode_guess(&candidates, opts.ranking(), &history)
+    } else {
+        s.best_guess(&candidates, opts.ranking())
+    };
+    match best {
+        Some(guess) => println!("{}", s.guesses[guess]),
+        None => {
+            eprintln!("No candidate answers remain");
+            process::exit(EXIT_NEGATIVE);
         }
-    }))
+    }
 }
 
-////////////////////////////////////////////////////////////////////////
-// The Scorer holds the data and caches scoring information
-//
+// Report whether any answer could give the feedback for the guess.
+fn check_feedback(s: &Scorer, guess: &str, feedback: &str) {
+    let guess = guess.to_ascii_lowercase();
+    let score = parse_score(feedback, s.word_len).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(EXIT_ERROR);
+    });
+    if s.is_feedback_possible(&guess, score) {
+        println!("Some answer gives {} for '{}'", render_score(score, s.word_len), guess);
+    } else {
+        println!("No answer gives {} for '{}'", render_score(score, s.word_len), guess);
+        process::exit(EXIT_NEGATIVE);
+    }
+}
 
-struct Scorer {
-    // Once the scores are precalculated, we refer to everything by indices.
-    guesses: Vec<String>,
-    answers: Vec<String>,
-    score_cache: Vec<Vec<u8>>,
-
-    // Awkward place to put reused vector.
-    bucket_vec: Vec<Vec<usize>>,
-}
-
-impl Scorer {
-    fn new() -> Scorer {
-        // Load the strings...
-        let mut guesses = std::fs::read_to_string("words/possible_guesses.txt")
-            .unwrap()
-            .lines()
-            .filter(|s| !s.is_empty())
-            .map(|s| String::from(s))
-            .collect::<Vec<String>>();
-        let answers = std::fs::read_to_string("words/possible_solutions.txt")
-            .unwrap()
-            .lines()
-            .filter(|s| !s.is_empty())
-            .map(|s| String::from(s))
-            .collect::<Vec<String>>();
-
-        // Answers are also possible guesses!
-        for answer in answers.iter() {
-            guesses.push(answer.clone());
+// Search for a strategy that always solves within the given number of
+// guesses, and report a first guess for it.
+fn exists_within(s: &Scorer, answer_idxs: &[usize], max: usize) {
+    match s.strategy_within(answer_idxs, max) {
+        Some(guess) => println!(
+            "A strategy exists to always solve within {} guesses, starting with '{}'",
+            max, s.guesses[guess]
+        ),
+        None => {
+            println!("No strategy always solves within {} guesses", max);
+            process::exit(EXIT_NEGATIVE);
         }
+    }
+}
 
-        // Score them all up-front.
-        let score_cache = guesses
-            .iter()
-            .map(|g| {
-                let gbs = g.as_bytes();
-                answers
-                    .iter()
-                    .map(|a| {
-                        let abs = a.as_bytes();
-                        score_wordle(gbs, abs)
-                    })
-                    .collect::<Vec<BucketId>>()
-            })
-            .collect::<Vec<Vec<BucketId>>>();
+// Report how many answers the opener leaves uniquely identified, so
+// they can be solved in exactly two guesses.
+fn two_count(s: &Scorer, opener: usize, answer_idxs: &[usize]) {
+    println!(
+        "{}: {} of {} answers solvable in exactly 2 guesses",
+        s.guesses[opener],
+        s.singleton_count(opener, answer_idxs),
+        answer_idxs.len()
+    );
+}
 
-        let bucket_vec = (0..MAX_BUCKET).map(|_| Vec::new()).collect::<Vec<_>>();
+// Print the average, worst case and histogram of guess counts.
+fn summarize(counts: &[usize]) {
+    let total: usize = counts.iter().sum();
+    let max = counts.iter().copied().max().unwrap_or(0);
+    let mean = total as f64 / counts.len() as f64;
+    let variance = counts.iter().map(|c| (*c as f64 - mean).powi(2)).sum::<f64>() / counts.len() as f64;
+    println!("Average guesses: {:.4}", mean);
+    println!("Standard deviation: {:.4}", variance.sqrt());
+    println!("Max guesses: {}", max);
+    for num_guesses in 1..=max {
+        let n = counts.iter().filter(|c| **c == num_guesses).count();
+        println!("{}: {}", num_guesses, n);
+    }
+    // The chances of keeping a streak going within each limit.
+    for limit in 3..=MAX_GUESSES {
+        let n = counts.iter().filter(|c| **c <= limit).count();
+        println!("<= {}: {:.2}%", limit, 100.0 * n as f64 / counts.len() as f64);
+    }
+}
+
+// List the answers by the number of guesses they took, alphabetically
+// within each.
+fn leaderboard(s: &Scorer, results: &[SolveResult], max_print: usize) {
+    let mut by_count: BTreeMap<usize, Vec<&str>> = BTreeMap::new();
+    for result in results.iter() {
+        by_count
+            .entry(result.num_guesses())
+            .or_default()
+            .push(s.answers[result.answer].as_str());
+    }
+    for (num_guesses, words) in by_count.iter_mut() {
+        words.sort_unstable();
+        println!("{} guesses ({}): {}", num_guesses, words.len(), list_words(words, " ", max_print));
+    }
+}
+
+// Greedily solve every candidate answer, returning the guess counts,
+// and logging the progress on every 'progress_every' answers.
+fn solve_counts(
+    s: &Scorer,
+    ranking: Ranking,
+    human: bool,
+    opener: Option<usize>,
+    answer_idxs: &[usize],
+    progress_every: usize,
+) -> Vec<usize> {
+    s.solve_all_with_progress(answer_idxs, ranking, human, opener, progress_every)
+        .iter()
+        .map(|result| result.num_guesses())
+        .collect()
+}
+
+// As summarize, as a single JSON object, also listing the answers that
+// couldn't be solved within Wordle's guess limit.
+fn json_summary(s: &Scorer, strategy: &str, answer_idxs: &[usize], counts: &[usize]) {
+    let total: usize = counts.iter().sum();
+    let max = counts.iter().copied().max().unwrap_or(0);
+    let histogram = (1..=max)
+        .map(|num_guesses| {
+            let n = counts.iter().filter(|c| **c == num_guesses).count();
+            format!("\"{}\": {}", num_guesses, n)
+        })
+        .collect::<Vec<_>>();
+    let failures = answer_idxs
+        .iter()
+        .zip(counts.iter())
+        .filter(|(_, count)| **count > MAX_GUESSES)
+        .map(|(target, _)| format!("\"{}\"", s.answers[*target]))
+        .collect::<Vec<_>>();
+    println!(
+        "{{ \"strategy\": \"{}\", \"average\": {:.4}, \"max\": {}, \"histogram\": {{ {} }}, \"failures\": [{}] }}",
+        strategy,
+        total as f64 / counts.len() as f64,
+        max,
+        histogram.join(", "),
+        failures.join(", ")
+    );
+}
 
-        Scorer {
-            guesses,
-            answers,
-            score_cache,
-            bucket_vec
+// Greedily solve every candidate answer, and report how well it went.
+// If 'second' is given, it's always played after the opener.
+fn self_solve(s: &Scorer, opts: &Opts, opener: Option<usize>, second: Option<usize>, answer_idxs: &[usize]) {
+    let ranking = opts.ranking();
+    let sorted;
+    let (opener, answer_idxs) = if opts.hardest_first {
+        let first_guess = opener.unwrap_or_else(|| s.first_guess(answer_idxs, ranking, opts.human));
+        sorted = s.answers_by_difficulty(first_guess, answer_idxs);
+        (Some(first_guess), sorted.as_slice())
+    } else {
+        (opener, answer_idxs)
+    };
+    let results = match (opener, second) {
+        (Some(opener), Some(second)) => {
+            s.solve_all_with_openers(answer_idxs, ranking, opts.human, &[opener, second], opts.progress_every)
         }
+        _ => s.solve_all_with_progress(answer_idxs, ranking, opts.human, opener, opts.progress_every),
+    };
+    let counts = results.iter().map(|result| result.num_guesses()).collect::<Vec<_>>();
+    if opts.leaderboard {
+        leaderboard(s, &results, opts.max_candidates_print);
     }
+    if opts.json_summary {
+        let strategy = if opts.human { String::from("human") } else { ranking.to_string() };
+        json_summary(s, &strategy, answer_idxs, &counts);
+    } else {
+        summarize(&counts);
+    }
+}
+
+// Play a game against the oracle, greedily, starting with the opener
+// if given. Returns the guesses and their feedback, exiting on error.
+fn play_game(
+    s: &Scorer,
+    ranking: Ranking,
+    opener: Option<usize>,
+    answer_idxs: &[usize],
+    oracle: &mut dyn Oracle,
+) -> Vec<(usize, BucketId)> {
+    let mut state = GameState::new(answer_idxs.to_vec());
+    let result = opener
+        .map_or(Ok(()), |opener| {
+            oracle.score(&s.guesses[opener]).and_then(|score| state.apply(s, opener, score))
+        })
+        .and_then(|_| state.play(s, ranking, oracle));
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        process::exit(EXIT_ERROR);
+    }
+    state.history().to_vec()
+}
 
-    // Given a guess, bucket the answer list entries by the score they return.
-    //
-    // What we'd like to do is have each bucket contain a single entry,
-    // indicating that the guess has uniquely identified all possibile
-    // answers. A bucket with more than one entry will require further
-    // guessing to identify a unique answer.
-    fn bucket_answers<'a>(&self, guess: usize, answers: &[usize]) -> Vec<Vec<usize>> {
-        let mut buckets = HashMap::new();
-
-        for answer in answers.iter() {
-            let score = self.score_cache[guess][*answer];
-            buckets
-                .entry(score)
-                .or_insert_with(|| Vec::new())
-                .push(*answer);
+// Print each guess of a game and its feedback.
+fn print_game(s: &Scorer, history: &[(usize, BucketId)]) {
+    for (guess, score) in history.iter() {
+        println!("{} {}", s.guesses[*guess], render_score(*score, s.word_len));
+    }
+}
+
+// Greedily solve a single answer, printing each guess and its score,
+// or, if 'share' is set, the emoji grid Wordle shares for the game.
+fn solve_one(s: &Scorer, ranking: Ranking, opener: Option<usize>, answer: &str, share: bool) {
+    let answer = answer.to_ascii_lowercase();
+    if !s.is_possible_answer(&answer) {
+        eprintln!("'{}' is not a possible answer", answer);
+        process::exit(EXIT_ERROR);
+    }
+    let answer_idxs = (0..s.answers.len()).collect::<Vec<_>>();
+    let mut oracle = KnownAnswer::new(&answer, s.rules);
+    let history = play_game(s, ranking, opener, &answer_idxs, &mut oracle);
+
+    if share {
+        println!("Wordle {} {}/{}", answer, history.len(), MAX_GUESSES);
+        println!();
+        for (_, score) in history.iter() {
+            println!("{}", render_score(*score, s.word_len));
         }
+    } else {
+        print_game(s, &history);
+    }
+}
 
-        let mut v: Vec<_> = buckets.into_iter().map(|(_k, v)| (v.len(), v)).collect();
-        v.sort_by(|a, b| b.cmp(a));
-        v.into_iter().map(|(_k, v)| v).collect()
+// Greedily play against any word, printing each guess and its score,
+// until it's guessed or no candidate answer could give the feedback.
+fn play_target(s: &Scorer, ranking: Ranking, opener: Option<usize>, answer_idxs: &[usize], target: &str) {
+    let target = target.to_ascii_lowercase();
+    let mut oracle = KnownAnswer::new(&target, s.rules);
+    let mut state = GameState::new(answer_idxs.to_vec());
+    while !state.is_solved(s) {
+        let guess = match opener {
+            Some(opener) if state.history().is_empty() => opener,
+            _ => state.suggest(s, ranking).unwrap(),
+        };
+        let score = oracle.score(&s.guesses[guess]).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(EXIT_ERROR);
+        });
+        println!("{} {}", s.guesses[guess], render_score(score, s.word_len));
+        if state.apply(s, guess, score).is_err() {
+            println!("Unsolvable: no candidate answer gives that feedback, so '{}' isn't one of them", target);
+            process::exit(EXIT_NEGATIVE);
+        }
     }
+    println!("Solved in {} guesses", state.history().len());
+}
 
-    // Version of bucket_answers used for the 3-guess case.
-    fn bucket_answers3<'a>(&mut self, guess: usize, answers: &[usize]) {
-        for bucket in self.bucket_vec.iter_mut() {
-            bucket.clear();
+// Greedily play a game for the target answer, allowing for one feedback
+// being wrong: when no candidate fits all the feedback, carry on with
+// those that fit all but one. The game carries on from 'history', the
+// guesses and feedback so far. The game is over once the target is
+// guessed, rather than once the feedback is all green, which wrong
+// feedback can be. Returns the whole history, and whether feedback had
+// to be doubted.
+fn play_doubting(
+    s: &Scorer,
+    ranking: Ranking,
+    opener: Option<usize>,
+    answer_idxs: &[usize],
+    target: usize,
+    oracle: &mut dyn Oracle,
+    mut history: Vec<(usize, BucketId)>,
+) -> Result<(Vec<(usize, BucketId)>, bool), WordleError> {
+    let mut candidates = answer_idxs.to_vec();
+    for (guess, score) in history.iter() {
+        candidates = s.filter_candidates(*guess, *score, &candidates);
+    }
+    let mut doubted = false;
+    loop {
+        let guessed = history.iter().map(|(guess, _)| *guess).collect::<Vec<_>>();
+        let guess = match opener {
+            Some(opener) if history.is_empty() => opener,
+            _ => s.best_guess_excluding(&candidates, ranking, &guessed).ok_or_else(|| {
+                WordleError::InvalidInput(String::from("No candidate answers remain"))
+            })?,
+        };
+        let score = oracle.score(&s.guesses[guess])?;
+        history.push((guess, score));
+        if s.guesses[guess] == s.answers[target] {
+            return Ok((history, doubted));
         }
+        candidates = s.filter_candidates(guess, score, &candidates);
+        if candidates.is_empty() {
+            doubted = true;
+            candidates = answer_idxs
+                .iter()
+                .copied()
+                .filter(|a| history.iter().filter(|(g, sc)| s.score(*g, *a) != *sc).count() <= 1)
+                .filter(|a| !guessed.contains(&s.guess_index(&s.answers[*a]).unwrap()))
+                .collect();
+        }
+    }
+}
 
-        for answer in answers.iter() {
-            let score = self.score_cache[guess][*answer];
-            self.bucket_vec[score as usize].push(*answer);
+// Play every answer greedily, getting one feedback tile wrong in a
+// game with probability p, and report how often the solver notices
+// (by no candidate fitting the feedback) and how often it still wins
+// within MAX_GUESSES. The wrong tile is in one of the feedbacks before
+// the answer's found, so games won by the opener are left alone. A
+// noisy game goes as the clean one did up to the wrong tile, so only
+// the rest of it is played again.
+fn noise(s: &Scorer, ranking: Ranking, opener: Option<usize>, answer_idxs: &[usize], p: f64, seed: u64) {
+    let play = |target: usize, oracle: &mut dyn Oracle, history: Vec<(usize, BucketId)>| {
+        play_doubting(s, ranking, opener, answer_idxs, target, oracle, history).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(EXIT_ERROR);
+        })
+    };
+    let mut rng = Rng::new(seed);
+    let (mut noisy, mut noticed, mut lost) = (0, 0, 0);
+    for target in answer_idxs.iter() {
+        let answer = &s.answers[*target];
+        let (clean, _) = play(*target, &mut KnownAnswer::new(answer, s.rules), Vec::new());
+        if clean.len() == 1 || !rng.chance(p) {
+            lost += (clean.len() > MAX_GUESSES) as usize;
+            continue;
         }
 
-        self.bucket_vec.sort_by(|a, b| b.len().cmp(&a.len()));
+        noisy += 1;
+        let turn = rng.below(clean.len() - 1);
+        let (position, other) = (rng.below(s.word_len), rng.below(2));
+        // The next feedback is the wrong one.
+        let mut oracle = NoisyOracle::new(KnownAnswer::new(answer, s.rules), 0, position, other);
+        let (history, doubted) = play(*target, &mut oracle, clean[..turn].to_vec());
+        noticed += doubted as usize;
+        lost += (history.len() > MAX_GUESSES) as usize;
     }
+    println!("{} of {} games had a wrong feedback tile", noisy, answer_idxs.len());
+    println!("{} of those had feedback that no candidate fitted", noticed);
+    println!(
+        "{} games ({:.2}%) were lost, not solved within {} guesses",
+        lost,
+        100.0 * lost as f64 / answer_idxs.len() as f64,
+        MAX_GUESSES
+    );
+}
 
-    // Optimise the order in which guesses are made, so that those
-    // that minimise the largest bucket come first.
-    fn optimise_guess_order(&mut self)  {
-        let answer_nums = self
-            .answers
-            .iter()
-            .enumerate()
-            .map(|(idx, _)| idx)
-            .collect::<Vec<usize>>();
+// Print the second guess to make after the opener for each of its
+// most common feedbacks, with the number of answers left, as a short
+// table to play from without the solver.
+fn cheat_sheet(s: &Scorer, ranking: Ranking, opener: usize, answer_idxs: &[usize]) {
+    println!("Open with {}. Then:", s.guesses[opener].to_ascii_uppercase());
+    println!();
+    println!("{:<10}  {:<5}  {:>4}", "Feedback", "Guess", "Left");
+    let buckets = s.bucket_answers(opener, answer_idxs);
+    let mut covered = 0;
+    // bucket_answers puts the largest first.
+    for bucket in buckets.iter().filter(|bucket| s.score(opener, bucket[0]) != s.correct_score()).take(CHEAT_SHEET_ROWS) {
+        let second = s.best_guess(bucket, ranking).unwrap();
+        // The squares are double width, so fill the column as it is.
+        println!(
+            "{}  {:<5}  {:>4}",
+            render_score(s.score(opener, bucket[0]), s.word_len),
+            s.guesses[second],
+            bucket.len()
+        );
+        covered += bucket.len();
+    }
+    println!();
+    println!("These cover {} of the {} answers.", covered, answer_idxs.len());
+}
 
-        let mut worst_cases: Vec<(usize, usize, String)> = self.guesses
-            .iter()
-            .enumerate()
-            .map(|(idx, guess)| {
-                // Bucket the answers by score for this guess.
-                let buckets = self.bucket_answers(idx, &answer_nums);
-                // Given bucketed answers, find the size of the largest
-                // bucket, which is a heuristic for the hardest case to
-                // solve.
-                let largest_bucket_size = buckets.iter().map(|v| v.len()).max().unwrap();
-                (largest_bucket_size, idx, guess.clone())
-            })
-            .collect();
-        worst_cases.sort();
+// Play against an adversary that keeps as many candidates as it can
+// with each reply, as in Absurdle.
+fn absurdle(s: &Scorer, ranking: Ranking, opener: Option<usize>, answer_idxs: &[usize]) {
+    let mut oracle = Adversary::new(s, answer_idxs.to_vec());
+    let history = play_game(s, ranking, opener, answer_idxs, &mut oracle);
+    print_game(s, &history);
+    println!("Solved in {} guesses", history.len());
+}
 
-        for (worst_case, _idx, guess) in worst_cases.iter() {
-            println!("{}: {}", worst_case, guess);
-        }
+// Play a game of a Wordle clone over HTTP, printing each guess and its
+// feedback.
+#[cfg(feature = "http")]
+fn play_http(s: &Scorer, ranking: Ranking, opener: Option<usize>, answer_idxs: &[usize], url: &str) {
+    let mut oracle = wordle_solver::HttpOracle::new(url);
+    print_game(s, &play_game(s, ranking, opener, answer_idxs, &mut oracle));
+}
 
-        // Sort the guess list and the score cache to match the
-        // improved search order.
-        self.guesses = worst_cases.iter().map(|(_, _, g)| g.clone()).collect();
-        self.score_cache = worst_cases
-            .iter()
-            .map(|(_, idx, _)| self.score_cache[*idx].clone())
-            .collect();
+// List the answers in the opener's largest buckets, biggest first: the
+// groups of answers it does the least to tell apart. If 'members' isn't
+// set, just give their sizes.
+fn show_worst_buckets(s: &Scorer, opener: usize, top: usize, members: bool, max_print: usize) {
+    let answer_idxs = (0..s.answers.len()).collect::<Vec<_>>();
+    // bucket_answers puts the largest first.
+    for (rank, bucket) in s.bucket_answers(opener, &answer_idxs).iter().take(top).enumerate() {
+        let label = if rank == 0 {
+            String::from("largest bucket")
+        } else {
+            format!("bucket #{} by size", rank + 1)
+        };
+        println!(
+            "{}: {} has {} answers, scoring {}{}",
+            s.guesses[opener],
+            label,
+            bucket.len(),
+            render_score(s.score(opener, bucket[0]), s.word_len),
+            if members { ":" } else { "" }
+        );
+        if members {
+            let words = bucket.iter().map(|a| s.answers[*a].as_str()).collect::<Vec<_>>();
+            println!("{}", list_words(&words, " ", max_print));
+        }
     }
 }
 
-////////////////////////////////////////////////////////////////////////
-// Depth-first search solver, biased towards trying best splitters first.
-//
+// Greedily solve every answer after the opener, and report the most
+// guesses needed, and which answers need them.
+fn worst_case(s: &Scorer, ranking: Ranking, opener: usize, progress_every: usize) {
+    let answer_idxs = (0..s.answers.len()).collect::<Vec<_>>();
+    let counts = solve_counts(s, ranking, false, Some(opener), &answer_idxs, progress_every);
+    let max = counts.iter().copied().max().unwrap_or(0);
+    let worst = answer_idxs
+        .iter()
+        .zip(counts.iter())
+        .filter(|(_, count)| **count == max)
+        .map(|(target, _)| *target)
+        .collect::<Vec<_>>();
+    println!(
+        "{}: at most {} guesses, e.g. for {} ({} answers need {})",
+        s.guesses[opener],
+        max,
+        s.answers[worst[0]],
+        worst.len(),
+        max
+    );
+}
 
-// Specialise last layers of search as an optimisation.
+// Play sampled multi-board games, and report how often every board
+// gets solved within the budget.
+fn multi_board(s: &Scorer, opts: &Opts, opener: Option<usize>, answer_idxs: &[usize], boards: usize) {
+    let ranking = opts.ranking();
+    let budget = opts.budget.unwrap_or(boards + 5);
+    let opener = opener.or_else(|| s.best_guess(answer_idxs, ranking));
+    let mut rng = Rng::new(opts.seed.unwrap_or(0));
 
-// Allocated once to optimise leaf case.
-pub static mut SEEN_TABLE: &'static mut [u8] = &mut [0; MAX_BUCKET];
-pub static mut COUNTER: u8 = 0;
+    let mut all_solved = 0;
+    let mut solved_guesses = 0;
+    for _ in 0..opts.samples {
+        let targets = rng.sample(answer_idxs, boards);
+        let outcome = s.solve_boards(&targets, answer_idxs, budget, ranking, opener);
+        let words = targets.iter().map(|t| s.answers[*t].as_str()).collect::<Vec<_>>();
+        eprintln!(
+            "{}: solved {}/{} in {} guesses",
+            words.join(","),
+            outcome.solved,
+            targets.len(),
+            outcome.guesses
+        );
+        if outcome.solved == targets.len() {
+            all_solved += 1;
+            solved_guesses += outcome.guesses;
+        }
+    }
 
-// Can we solve with 2 guesses? 2nd guess must be correct answer, which
-// means all we need to do is check that the first guess full determines
-// - there can be at most one possible solution per bucket.
-fn can_solve2(s: &Scorer, answers: &[usize]) -> bool {
-    (0..s.guesses.len()).any(|guess| can_solve_with_guess2(s, guess, answers))
+    println!(
+        "{} boards, budget {}: solved all boards in {} of {} sampled games",
+        boards, budget, all_solved, opts.samples
+    );
+    if all_solved > 0 {
+        println!(
+            "Average guesses when solved: {:.4}",
+            solved_guesses as f64 / all_solved as f64
+        );
+    }
 }
 
-fn can_solve_with_guess2(
-    s: &Scorer,
-    guess: usize,
-    answers: &[usize]
-) -> bool {
-    unsafe {
-        // Special case - next guess has to be final, so check if each bucket
-        // contains at most one entry.
-        //
-        // Set counter to a value not seen in the array.
-        if COUNTER == u8::MAX {
-            COUNTER = 0;
-            for entry in SEEN_TABLE.iter_mut() {
-                *entry = 255;
-            }
-        } else {
-            COUNTER += 1;
-        }
+// Print a table of the best opener after removing each answer in the
+// file in turn, one row each time it changes. Answers already ruled
+// out are skipped.
+fn opener_drift(s: &Scorer, answer_idxs: &[usize], removed: &Path) {
+    let removed = s.read_candidates(removed).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(EXIT_ERROR);
+    });
+    let mut seen = vec![false; s.answers.len()];
+    for answer in answer_idxs.iter() {
+        seen[*answer] = true;
+    }
+    let removed = removed
+        .into_iter()
+        .filter(|answer| std::mem::replace(&mut seen[*answer], false))
+        .collect::<Vec<_>>();
 
-        // Iterate over the answers, early-outing if a bucket is used twice.
-        for answer in answers.iter() {
-            let score = s.score_cache[guess][*answer];
-            if SEEN_TABLE[score as usize] == COUNTER {
-                return false;
-            }
-            SEEN_TABLE[score as usize] = COUNTER;
+    println!("{:>7}  {:>6}  {:>8}", "Removed", "Opener", "Expected");
+    let drift = s.opener_drift(answer_idxs, &removed).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(EXIT_ERROR);
+    });
+    for (num_removed, (opener, expected)) in drift.iter().enumerate() {
+        if num_removed == 0 || drift[num_removed - 1].0 != *opener || num_removed == removed.len() {
+            println!("{:>7}  {:>6}  {:>8.2}", num_removed, s.guesses[*opener], expected);
         }
-        true
     }
 }
 
-fn can_solve3(
-    s: &mut Scorer,
-    answers: &[usize]
-) -> bool {
-    (0..s.guesses.len())
-        .any(|guess| {
-            s.bucket_answers3(guess, answers);
-            s.bucket_vec.iter().all(|v| {
-                v.is_empty() || can_solve2(s, &v)
+// Report the distribution of the number of candidates remaining after
+// always opening with the same pair of guesses.
+fn pair_distribution(s: &Scorer, answer_idxs: &[usize], first: &str, second: &str) {
+    let guesses = [first, second]
+        .iter()
+        .map(|word| {
+            s.guess_index(&word.to_ascii_lowercase()).unwrap_or_else(|| {
+                eprintln!("'{}' is not in the word list", word);
+                process::exit(EXIT_ERROR);
             })
         })
+        .collect::<Vec<_>>();
+
+    // Answers that are one of the openers don't make it to guess 3.
+    let mut solved = 0;
+    let mut histogram = BTreeMap::new();
+    for part in s.partition_by_guesses(&guesses, answer_idxs) {
+        if part.len() == 1 && guesses.iter().any(|g| s.guesses[*g] == s.answers[part[0]]) {
+            solved += 1;
+        } else {
+            *histogram.entry(part.len()).or_insert(0) += part.len();
+        }
+    }
+
+    let unsolved = answer_idxs.len() - solved;
+    let total: usize = histogram.iter().map(|(size, count)| size * count).sum();
+    println!("Candidates remaining for guess 3, after {} then {}:", first, second);
+    println!("Solved already: {}", solved);
+    for (size, count) in histogram.iter() {
+        println!("{} remaining: {} answers", size, count);
+    }
+    // Nothing left for guess 3 means nothing to average.
+    if unsolved > 0 {
+        println!("Average remaining: {:.4}", total as f64 / unsolved as f64);
+    }
 }
 
-// General solver
-//
-// Can we, in the given number of guesses, uniquely identify the
-// solution from the given answer list? Guesses should be sorted to
-// put best splitters first to make finding answers faster.
-fn can_solve(
-    s: &mut Scorer,
-    num_guesses: usize,
-    answers: &[usize]
-) -> bool {
-    if num_guesses == 3 {
-        return can_solve3(s, answers)
-    } else if num_guesses == 2 {
-        return can_solve2(s, answers)
-    }
-
-    for idx in 0..s.guesses.len() {
-        eprint!(
-            " {:5} {:5}/{:5}\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08\x08",
-            s.guesses[idx],
-            idx,
-            s.guesses.len());
-
-            let buckets = s.bucket_answers(idx, answers);
-        if buckets.iter().all(|v| { can_solve(s, num_guesses - 1, &v) }) {
-            return true;
+// Solve every answer both greedily and optimally, and report where the
+// greedy strategy does worse, then how both compare with the minimax
+// strategy, within MAX_GUESSES. The searches are exhaustive, so this is
+// only practical on small candidate sets, unless given a budget.
+fn regret(
+    s: &Scorer,
+    ranking: Ranking,
+    answer_idxs: &[usize],
+    budget: Option<usize>,
+    checkpoint: Option<&Path>,
+) {
+    let mut solver = match budget {
+        Some(budget) => OptimalSolver::with_budget(s, budget),
+        None => OptimalSolver::new(s),
+    };
+    if let Some(path) = checkpoint {
+        solver.resume_from(path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(EXIT_ERROR);
+        });
+    }
+    let mut greedy_total = 0;
+    let mut optimal_total = 0;
+    let mut greedy_worst = 0;
+    let mut optimal_worst = 0;
+    for target in answer_idxs.iter() {
+        let greedy = s.guess_greedily(*target, answer_idxs, ranking);
+        let optimal = solver.guesses_for(*target, answer_idxs);
+        if greedy > optimal {
+            println!(
+                "{}: greedy {}, optimal {} (+{})",
+                s.answers[*target],
+                greedy,
+                optimal,
+                greedy - optimal
+            );
         }
+        greedy_total += greedy;
+        optimal_total += optimal;
+        greedy_worst = greedy_worst.max(greedy);
+        optimal_worst = optimal_worst.max(optimal);
+    }
+    let n = answer_idxs.len() as f64;
+    println!(
+        "Average guesses: greedy {:.4}, optimal {:.4}",
+        greedy_total as f64 / n,
+        optimal_total as f64 / n
+    );
+    println!("Worst case: greedy {}, optimal {}", greedy_worst, optimal_worst);
+
+    // Minimax plays for the worst case rather than the average. Its
+    // average is of the strategy it found, not the best with that worst
+    // case, so it isn't directly comparable with the optimal one.
+    match s.solve_optimally(answer_idxs, MAX_GUESSES, budget) {
+        (Some(minimax), _) => println!(
+            "Minimax: worst case {}, average guesses {:.4} for the strategy found",
+            minimax.worst_case, minimax.average
+        ),
+        (None, true) => println!("Minimax: not solvable within {} guesses", MAX_GUESSES),
+        (None, false) => println!("Minimax: search budget ran out before finding a strategy"),
+    }
+    if let Some(path) = checkpoint {
+        if let Err(e) = solver.save_checkpoint(path) {
+            eprintln!("{}", e);
+            process::exit(EXIT_ERROR);
+        }
+    }
+    if !solver.is_proven() {
+        println!("Search budget ran out, so the optimal results are only the best found");
     }
-    false
 }
 
-////////////////////////////////////////////////////////////////////////
-// Top-level copy of solver, with more diagnostic spam
-//
+// Summarise the information every opener gives against the answers,
+// in bits: the range, the mean, and a histogram in 'bins' equal steps,
+// to show how far the best openers stand out.
+fn entropy_distribution(s: &Scorer, answer_idxs: &[usize], bins: usize) {
+    let entropies = s.entropies(answer_idxs);
+    let min = entropies.iter().copied().fold(f64::INFINITY, f64::min);
+    let (best, max) = s.find_max_entropy(answer_idxs).unwrap();
+    let mean = entropies.iter().sum::<f64>() / entropies.len() as f64;
+    println!(
+        "{} openers: min {:.2} bits, max {:.2} bits ({}), mean {:.2} bits",
+        entropies.len(),
+        min,
+        max,
+        s.guesses[best],
+        mean
+    );
 
-fn can_solve_noisy(
-    s: &mut Scorer,
-    num_guesses: usize,
-    answers: &[usize],
-    shard_index: usize,
-    shard_count: usize,
-) -> bool {
-    for idx in 0..s.guesses.len() {
-        if idx % shard_count != shard_index {
-            continue;
+    let bins = bins.max(1);
+    let width = (max - min) / bins as f64;
+    let mut counts = vec![0; bins];
+    for entropy in entropies.iter() {
+        let bin = if width > 0.0 { ((entropy - min) / width) as usize } else { 0 };
+        counts[bin.min(bins - 1)] += 1;
+    }
+    for (bin, count) in counts.iter().enumerate() {
+        let low = min + bin as f64 * width;
+        println!("{:>5.2}-{:<5.2} {:>6}", low, low + width, count);
+    }
+}
+
+// Print how many answers each letter appears in, overall and in each
+// position, and how much of that a given word covers.
+fn letter_stats(s: &Scorer, answer_idxs: &[usize], word: Option<&str>) {
+    let alphabet = &s.alphabet;
+    let mut overall = vec![0usize; alphabet.len()];
+    let mut by_position = vec![vec![0usize; s.word_len]; alphabet.len()];
+    for answer in answer_idxs.iter() {
+        let bytes = s.answers[*answer].as_bytes();
+        let mut seen = vec![false; alphabet.len()];
+        for (pos, c) in bytes.iter().enumerate() {
+            let letter = alphabet.index(*c);
+            by_position[letter][pos] += 1;
+            if !seen[letter] {
+                seen[letter] = true;
+                overall[letter] += 1;
+            }
         }
-        eprintln!("Trying guess {} ({}/{})", s.guesses[idx], idx, s.guesses.len());
-        if can_solve_with_guess_noisy(s, idx, num_guesses, answers) {
-            return true;
+    }
+
+    let mut letters = (0..alphabet.len()).collect::<Vec<usize>>();
+    letters.sort_by_key(|letter| std::cmp::Reverse(overall[*letter]));
+    let header = (1..=s.word_len).map(|pos| format!("{:>6}", pos)).collect::<String>();
+    println!("Letter  Answers{}", header);
+    for letter in letters.iter() {
+        let positions = by_position[*letter].iter().map(|n| format!("{:>6}", n)).collect::<String>();
+        println!("{:>6}  {:>7}{}", alphabet.letter(*letter) as char, overall[*letter], positions);
+    }
+
+    if let Some(word) = word {
+        let word = word.to_ascii_lowercase();
+        if word.len() != s.word_len || !word.bytes().all(|c| alphabet.contains(c)) {
+            eprintln!("'{}' should be {} letters from the word list", word, s.word_len);
+            process::exit(EXIT_ERROR);
         }
+        let mut distinct = word.bytes().collect::<Vec<_>>();
+        distinct.sort_unstable();
+        distinct.dedup();
+        let coverage: usize = distinct.iter().map(|c| overall[alphabet.index(*c)]).sum();
+        let positional: usize = word
+            .bytes()
+            .enumerate()
+            .map(|(pos, c)| by_position[alphabet.index(c)][pos])
+            .sum();
+        println!(
+            "{}: summed coverage {}, of which {} in the same position",
+            word, coverage, positional
+        );
     }
-    false
 }
 
-fn can_solve_with_guess_noisy(
-    s: &mut Scorer,
-    guess: usize,
-    num_guesses: usize,
-    answers: &[usize]
-) -> bool {
-    let buckets = s.bucket_answers(guess, answers);
+// List the groups of answers that are anagrams of each other, largest
+// first.
+fn anagrams(s: &Scorer, max_print: usize) {
+    let mut clusters = s.anagram_clusters();
+    clusters.sort_by_key(|cluster| std::cmp::Reverse(cluster.len()));
+    let lines = clusters
+        .iter()
+        .map(|cluster| cluster.iter().map(|a| s.answers[*a].as_str()).collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>();
+    let lines = lines.iter().map(|line| line.as_str()).collect::<Vec<_>>();
+    println!("{} groups of anagrams among the answers:", clusters.len());
+    println!("{}", list_words(&lines, "\n", max_print));
+}
 
-    for (idx, bucket) in buckets.iter().enumerate() {
-        eprint!("    Bucket {}/{} (size {})... ", idx, buckets.len(), bucket.len());
-        assert_eq!(num_guesses - 1, 3);
-        let soluble = can_solve(s, num_guesses - 1, &bucket);
-        if soluble {
-            eprintln!("solved");
-        } else {
-            eprintln!("insoluble");
-            return false;
+// Print the pairs of answers that only a guess outside the answers (or
+// of one of the pair) tells apart.
+fn answer_only_pairs(s: &Scorer, max_print: usize) {
+    let pairs = s.answer_only_indistinguishable_pairs();
+    if pairs.is_empty() {
+        println!("Every pair of answers is told apart by some other answer, so guessing only answers is safe.");
+        return;
+    }
+    let lines = pairs
+        .iter()
+        .map(|(a, b)| format!("{} {}", s.answers[*a], s.answers[*b]))
+        .collect::<Vec<_>>();
+    let lines = lines.iter().map(|line| line.as_str()).collect::<Vec<_>>();
+    println!("{} pairs of answers no other answer tells apart:", pairs.len());
+    println!("{}", list_words(&lines, "\n", max_print));
+}
+
+// Print a greedily-found set of guesses that separates the answers,
+// and how many answers it leaves ambiguous.
+fn separating_set(s: &Scorer) {
+    let set = s.separating_set_greedy();
+    let words = set.iter().map(|g| s.guesses[*g].as_str()).collect::<Vec<_>>();
+    println!("{} guesses separate the answers: {}", set.len(), words.join(" "));
+    let all = (0..s.answers.len()).collect::<Vec<_>>();
+    let ambiguous = s
+        .partition_by_guesses(&set, &all)
+        .iter()
+        .filter(|part| part.len() > 1)
+        .map(|part| part.len())
+        .sum::<usize>();
+    println!("{} answers remain ambiguous", ambiguous);
+}
+
+// Print the word's rank as an opener by each metric.
+fn opener_ranks(s: &Scorer, word: &str) {
+    let ranks = s.opener_ranks(&word.to_ascii_lowercase()).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(EXIT_ERROR);
+    });
+    println!("Ranks of {} among {} openers:", word, ranks.num_guesses);
+    let rows = [
+        ("Worst case", &ranks.worst_case, 1.0),
+        ("Entropy", &ranks.entropy, -1.0),
+        ("Expected", &ranks.expected_remaining, 1.0),
+        ("Buckets", &ranks.num_buckets, -1.0),
+    ];
+    for (label, rank, sign) in rows.iter() {
+        let best = if rank.can_be_best() { ", can be best" } else { "" };
+        println!(
+            "{:>10}: {:>8.2}, rank {} ({} tied){}",
+            label,
+            sign * rank.value,
+            rank.rank,
+            rank.ties,
+            best
+        );
+    }
+}
+
+// Print a table of the best openers by the ranking, with their metrics.
+// If 'common' is given, only consider openers of distinct letters
+// among that many of the most common.
+fn openers(s: &Scorer, ranking: Ranking, answer_idxs: &[usize], top: usize, common: Option<usize>) {
+    let metrics = s.guess_metrics(answer_idxs).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(EXIT_ERROR);
+    });
+    let candidates = match common {
+        Some(num_letters) => s.common_letter_guesses(answer_idxs, num_letters),
+        None => (0..s.guesses.len()).collect(),
+    };
+    let mut ranked = candidates
+        .into_iter()
+        .map(|guess| (metrics.rank(ranking, guess), guess))
+        .collect::<Vec<_>>();
+    ranked.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    println!("Under {} rules:", s.rules);
+    println!("{:>5}  {:<5}  {:>10}  {:>7}  {:>9}", "Rank", "Word", "Worst case", "Buckets", "Expected");
+    for (rank, (_, guess)) in ranked.iter().take(top).enumerate() {
+        println!(
+            "{:>5}  {:<5}  {:>10}  {:>7}  {:>9.2}",
+            rank + 1,
+            s.guesses[*guess],
+            metrics.worst_case(*guess),
+            metrics.bucket_count(*guess),
+            metrics.expected_remaining(*guess)
+        );
+    }
+}
+
+// Compare the guesses picked by maximum entropy and by minimax (the
+// smallest worst case) on the candidate sets left after the opener,
+// reporting how often they differ, and by how much on each metric.
+fn disagreement(s: &Scorer, opener: Option<usize>, answer_idxs: &[usize]) {
+    let opener = opener.unwrap_or_else(|| s.best_guess(answer_idxs, Ranking::WorstCase).unwrap());
+    let states = s
+        .bucket_answers(opener, answer_idxs)
+        .into_iter()
+        .filter(|bucket| bucket.len() > 2)
+        .collect::<Vec<_>>();
+
+    let mut differ = 0;
+    let mut entropy_gap = 0.0;
+    let mut worst_case_gap = 0;
+    for candidates in states.iter() {
+        let (by_entropy, _) = s.find_max_entropy(candidates).unwrap();
+        // Straight from the ranking, as best_guess may be set up to play
+        // the endgame some other way. Candidates win ties, as there.
+        let tied = s.best_guesses(candidates, Ranking::WorstCase);
+        let by_minimax = tied
+            .iter()
+            .find(|guess| candidates.iter().any(|a| s.answers[*a] == s.guesses[**guess]))
+            .copied()
+            .unwrap_or(tied[0]);
+        if s.guesses[by_entropy] != s.guesses[by_minimax] {
+            differ += 1;
+            entropy_gap += s.entropy(by_entropy, candidates) - s.entropy(by_minimax, candidates);
+            worst_case_gap += s.find_greedy_worst_case(by_entropy, candidates)
+                - s.find_greedy_worst_case(by_minimax, candidates);
         }
     }
-    true
+
+    println!(
+        "After {}, entropy and minimax pick different guesses for {} of {} candidate sets",
+        s.guesses[opener],
+        differ,
+        states.len()
+    );
+    if differ > 0 {
+        println!(
+            "Where they differ, the entropy pick has {:.3} more bits on average, and a worst case {:.3} larger",
+            entropy_gap / differ as f64,
+            worst_case_gap as f64 / differ as f64
+        );
+    }
+}
+
+// Show how a single guess scores against an answer.
+fn debug_score(rules: ScoringRules, guess: &str, answer: &str) {
+    if guess.len() != answer.len() || guess.is_empty() || guess.len() > MAX_WORD_LEN {
+        eprintln!("Words must be the same length, of 1 to {} letters", MAX_WORD_LEN);
+        process::exit(EXIT_ERROR);
+    }
+    let (guess, answer) = (guess.to_ascii_lowercase(), answer.to_ascii_lowercase());
+    let score = score_with_rules(rules, guess.as_bytes(), answer.as_bytes());
+    println!("Packed: {}", score);
+    println!("Decoded: {:?}", decode_score(score, guess.len()));
+    println!("Rendered: {}", render_score(score, guess.len()));
 }
 
 ////////////////////////////////////////////////////////////////////////
@@ -349,6 +989,9 @@ fn can_solve_with_guess_noisy(
 #[derive(Parser)]
 #[clap(version = "0.1", author = "Simon Frankau <sgf@arbitrary.name>")]
 #[clap(about = "Wordle solver solver")]
+#[clap(after_help = "Results are printed to stdout, and progress and errors to stderr. \
+Exits with status 0 on success, 1 if the answer is negative (e.g. no candidates remain), \
+and 2 on error.")]
 struct Opts {
     /// Shard count. Defaults to 1.
     #[clap(long, default_value = "1")]
@@ -356,75 +999,650 @@ struct Opts {
     /// Shard index. Should be between 0 and shard count - 1. Defaults to 0.
     #[clap(long, default_value = "0")]
     shard_index: usize,
+    /// Only consider answers matching a pattern of known letters, with
+    /// '.' for unknown letters, e.g. ".ight". A position may also be a
+    /// class of letters, as in "[aeiou]..[^xyz]." for a vowel first and
+    /// anything but x, y or z fourth.
+    #[clap(long, visible_alias = "answer-pattern")]
+    pattern: Option<String>,
+    /// File of valid guesses, one per line. Defaults to
+    /// possible_guesses.txt in $WORDLE_WORDS_DIR, or in "words".
+    #[clap(long)]
+    guesses: Option<PathBuf>,
+    /// File of possible answers, one per line. Defaults to
+    /// possible_solutions.txt in $WORDLE_WORDS_DIR, or in "words".
+    #[clap(long)]
+    solutions: Option<PathBuf>,
+
+    /// Only consider the answers listed in this file, one per line.
+    #[clap(long)]
+    candidates_file: Option<PathBuf>,
+    /// Never guess the words listed in this file, one per line. Any
+    /// that are possible answers still have to be guessed to win, so
+    /// are kept, with a warning.
+    #[clap(long)]
+    block: Option<PathBuf>,
+    /// Rank guesses for the search order, and in the openers table, by
+    /// how they split just the answers listed in this file, one per
+    /// line. Other answers are still possible.
+    #[clap(long)]
+    metric_answers: Option<PathBuf>,
+    /// Skip words in the --candidates-file that aren't in the answer
+    /// list, rather than failing.
+    #[clap(long, requires = "candidates-file")]
+    skip_unknown: bool,
+
+    /// How to rank guesses: "worst-case" (size of the largest bucket),
+    /// "expected-remaining" (expected number of candidates left) or
+    /// "residual-worst-case" (most candidates left after the next guess
+    /// as well).
+    #[clap(long, default_value = "worst-case")]
+    ranking: Ranking,
+    /// Rank guesses by the number of distinct feedback patterns they
+    /// produce. Short for "--ranking maximize-partitions".
+    #[clap(long, conflicts_with = "ranking")]
+    maximize_partitions: bool,
+    /// Greedily solve every answer, and report the guess counts.
+    #[clap(long)]
+    self_solve: bool,
+    /// With --self-solve, play like a typical player: after the opener,
+    /// only ever guess a candidate, the one with the most common letters.
+    #[clap(long, requires = "self-solve")]
+    human: bool,
+    /// With --self-solve, solve the answers hardest first: those sharing
+    /// the biggest buckets after the opener.
+    #[clap(long, requires = "self-solve")]
+    hardest_first: bool,
+    /// When solving every answer, log the progress on only every this
+    /// many answers, or not at all if 0.
+    #[clap(long, default_value = "100")]
+    progress_every: usize,
+    /// Write that progress to stderr at most once per this many
+    /// answers, which is faster in a terminal. 1 shows each answer's
+    /// progress as it happens.
+    #[clap(long, default_value = "50")]
+    log_batch: usize,
+    /// With --self-solve, also list the answers grouped by the number of
+    /// guesses they took.
+    #[clap(long, requires = "self-solve")]
+    leaderboard: bool,
+    /// Print the --self-solve summary as a single JSON object.
+    #[clap(long, requires = "self-solve")]
+    json_summary: bool,
+    /// Break ties between equally good guesses by the order of the
+    /// guess list, e.g. one listing common words first, rather than the
+    /// solver's own order.
+    #[clap(long)]
+    prefer_input_order: bool,
+    /// Play by Wordle's hard mode rules, only making guesses that reuse
+    /// every green in place and every yellow somewhere, when solving
+    /// greedily with --self-solve and suggesting guesses for a game.
+    #[clap(long)]
+    hard_mode: bool,
+    /// Once this few candidates remain, pick the guess that minimises
+    /// the expected number of guesses exactly, rather than by ranking.
+    #[clap(long, default_value = "0")]
+    endgame: usize,
+    /// Once this few candidates remain, only guess answers, scored
+    /// from a compact answers-by-answers table.
+    #[clap(long, default_value = "0")]
+    answer_endgame: usize,
+
+    /// Play this many boards at once, sharing guesses, as in Quordle.
+    #[clap(long)]
+    boards: Option<usize>,
+    /// Total guesses allowed across all the boards. Defaults to the
+    /// number of boards plus 5.
+    #[clap(long, requires = "boards")]
+    budget: Option<usize>,
+    /// Number of random games to sample, where analysing every game is
+    /// impractical.
+    #[clap(long, default_value = "100")]
+    samples: usize,
+    /// Seed for random sampling, --random-opener and --practice. Sampling
+    /// defaults to 0, so runs are comparable, while --random-opener and
+    /// --practice default to the time, so vary from session to session.
+    #[clap(long)]
+    seed: Option<u64>,
+    /// Suggest guesses for a real game, reading back the feedback.
+    #[clap(long)]
+    interactive: bool,
+    /// Answer commands read line by line from stdin, for other programs
+    /// to play a game through. See the README for the protocol.
+    #[clap(long, conflicts_with = "interactive")]
+    server: bool,
+    /// Practise working out feedback: the solver plays against a random
+    /// answer (see --seed), and you give the feedback for each
+    /// of its guesses, which is checked against the real thing.
+    #[clap(long, conflicts_with_all = &["interactive", "server"])]
+    practice: bool,
+    /// With --interactive, explain each suggestion: how it splits the
+    /// candidates, and how many could remain.
+    #[clap(long, requires = "interactive")]
+    explain: bool,
+    /// With --interactive, show the top 5 guesses each turn, with their
+    /// largest bucket, bucket count and expected candidates remaining.
+    #[clap(long, requires = "interactive")]
+    assist: bool,
+    /// Greedily solve this answer, printing the guesses made.
+    #[clap(long, conflicts_with = "pattern")]
+    solve: Option<String>,
+    /// Greedily play against this word, which needn't be a possible
+    /// answer, printing the guesses made. If it isn't an answer, this
+    /// shows how the solver fails.
+    #[clap(long, conflicts_with_all = &["solve", "pattern"])]
+    target: Option<String>,
+    /// With --solve, print the result as Wordle's shareable emoji grid.
+    #[clap(long, requires = "solve")]
+    share: bool,
+    /// Print a one-page cheat sheet: the opener (the best, if not
+    /// given), and the second guess to make for each of its 20 most
+    /// likely feedbacks.
+    #[clap(long)]
+    cheat_sheet: bool,
+    /// Greedily solve every answer, but with this chance in each game
+    /// of one tile of one feedback being wrong, picked with --seed, and
+    /// report how often the solver, allowing for a mistake once no
+    /// candidate fits, still wins within 6 guesses.
+    #[clap(long)]
+    noise: Option<f64>,
+    /// Play against an adversary that picks the answer as late as it can,
+    /// always giving the feedback that leaves the most candidates.
+    #[clap(long)]
+    absurdle: bool,
+    /// Play a Wordle clone over HTTP, POSTing guesses to this URL.
+    #[cfg(feature = "http")]
+    #[clap(long)]
+    http_url: Option<String>,
+    /// Opening guess to use in analysis modes, including --self-solve,
+    /// and to suggest first with --interactive.
+    #[clap(long)]
+    opener: Option<String>,
+    /// With --self-solve, always make this second guess after the
+    /// opener, whatever the opener's feedback.
+    #[clap(long, requires_all = &["opener", "self-solve"])]
+    second: Option<String>,
+    /// Use a random opener from the best quarter of the guesses under
+    /// the ranking, picked with --seed, in place of --opener.
+    #[clap(long, conflicts_with = "opener")]
+    random_opener: bool,
+    /// Greedily solve every answer after the opener, and report the
+    /// most guesses needed, and for which answers.
+    #[clap(long, requires = "opener", conflicts_with = "pattern")]
+    worst_case: bool,
+    /// List the answers in the opener's largest feedback bucket.
+    #[clap(long, requires = "opener", conflicts_with = "pattern")]
+    show_worst_bucket: bool,
+    /// With --show-worst-bucket, show this many of the largest buckets,
+    /// biggest first, rather than just the one.
+    #[clap(long, requires = "show-worst-bucket")]
+    top_buckets: Option<usize>,
+    /// With --show-worst-bucket, only give the sizes of the buckets, not
+    /// the answers in them.
+    #[clap(long, requires = "show-worst-bucket")]
+    sizes_only: bool,
+    /// Count the answers solvable in exactly 2 guesses after the opener.
+    #[clap(long, requires = "opener")]
+    two_count: bool,
+    /// Write the guesses, best first, to this file once they've been
+    /// ordered for the search.
+    #[clap(long)]
+    dump_order: Option<PathBuf>,
+    /// Write the score of every guess against every answer to this
+    /// file, as binary for other tools, and exit. The word order goes
+    /// alongside, with ".words" added to the name. See
+    /// Scorer::write_score_matrix for the format.
+    #[clap(long)]
+    export_matrix: Option<PathBuf>,
+
+    /// Once the guesses are ordered for the search, show how this many
+    /// of the best split the answers: the number of buckets of each
+    /// size.
+    #[clap(long, default_value = "0", conflicts_with = "load-order")]
+    trace_optimise: usize,
+
+    /// Read the guess order for the search from a file written by
+    /// --dump-order, rather than recalculating it.
+    #[clap(long)]
+    load_order: Option<PathBuf>,
+
+    /// Most words to print in any list of words.
+    #[clap(long, default_value = "50")]
+    max_candidates_print: usize,
+
+    /// Allow any guess to be the answer, not just the curated answer
+    /// list, for Wordle clones. Uses much more memory and time.
+    #[clap(long)]
+    open_dictionary: bool,
+
+    /// Drop guesses that aren't the right length, rather than failing,
+    /// to use a dictionary of words of all lengths.
+    #[clap(long)]
+    filter_guess_length: bool,
+
+    /// Only cache the scores of this many of the best openers, and the
+    /// answers, scoring other guesses as needed. Saves memory, at the
+    /// cost of speed.
+    #[clap(long)]
+    cached_guesses: Option<usize>,
+
+    /// Scoring rules, for Wordle clones: "standard", "greens-reusable"
+    /// (greens don't use up a letter for yellows), "unlimited-yellows"
+    /// (a letter anywhere in the answer is always yellow), or
+    /// "wildcard:C" (C in an answer matches any letter).
+    #[clap(long, default_value = "standard")]
+    rules: ScoringRules,
+
+    /// Print the score of a guess against an answer, both encoded and
+    /// decoded, and exit.
+    #[clap(long, number_of_values = 2, value_names = &["GUESS", "ANSWER"])]
+    debug_score: Option<Vec<String>>,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+impl Opts {
+    fn ranking(&self) -> Ranking {
+        if self.maximize_partitions {
+            Ranking::MaximizePartitions
+        } else {
+            self.ranking
+        }
+    }
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the best next guess for a game in progress. Exits with
+    /// status 1 if no candidate answers remain.
+    Next {
+        /// The game so far, as space-separated "guess:feedback" pairs.
+        /// Feedback has a letter per position: g(reen), y(ellow) or
+        /// a(bsent), e.g. "crane:aayaa salet:gaaya", or the score as
+        /// encoded by the solver, e.g. "crane:121".
+        #[clap(long, default_value = "")]
+        history: String,
+    },
+    /// Check whether any answer gives the feedback for the guess, to
+    /// catch mistyped feedback. Exits with status 1 if none does.
+    CheckFeedback {
+        guess: String,
+        /// A letter per position: g(reen), y(ellow) or a(bsent).
+        feedback: String,
+    },
+    /// Show how many candidates remain entering guess 3 after always
+    /// opening with the same two guesses, as a histogram over answers.
+    PairDistribution {
+        first: String,
+        second: String,
+    },
+    /// Show how the best opener by expected remaining changes as the
+    /// answers listed in a file are removed, one at a time in order, as
+    /// when past answers are ruled out.
+    OpenerDrift {
+        /// The answers to remove, one per line, in order.
+        removed: PathBuf,
+    },
+    /// Compare greedy and optimal solving on every answer, listing the
+    /// answers where greedy needs more guesses, then the worst cases
+    /// and averages against the minimax strategy's, within 6 guesses.
+    /// Only practical on small answer sets, e.g. narrowed with --pattern.
+    Regret {
+        /// Give up the optimal and minimax searches after visiting this
+        /// many sets of candidates each, and use the best found so far.
+        #[clap(long)]
+        optimal_budget: Option<usize>,
+        /// Resume the optimal search from this file if it exists, and
+        /// save progress to it, for long searches. The file records the
+        /// word lists and rules, and can only be resumed with the same.
+        #[clap(long)]
+        checkpoint: Option<PathBuf>,
+    },
+    /// Find whether some strategy always solves within a number of
+    /// guesses, and print a first guess for it. Exits with status 1 if
+    /// there is none.
+    ExistsWithin {
+        /// Maximum number of guesses, including the final correct one.
+        max: usize,
+    },
+    /// Print the best chance of solving within a number of guesses,
+    /// playing to maximise it rather than for the best average. Only
+    /// practical on small answer sets, e.g. narrowed with --pattern.
+    WinProbability {
+        /// Guesses allowed, including the final correct one.
+        #[clap(default_value = "6")]
+        budget: usize,
+    },
+    /// Print a table of the best openers under the ranking, with the
+    /// size of their largest bucket, number of buckets and expected
+    /// candidates remaining.
+    Openers {
+        /// Number of openers to show.
+        #[clap(long, default_value = "20")]
+        top: usize,
+        /// Only show openers of five different letters, all among this
+        /// many of the letters found in the most answers.
+        #[clap(long)]
+        common: Option<usize>,
+    },
+    /// Show where a word ranks as an opener by each metric, and whether
+    /// it could ever be the best.
+    OpenerRanks {
+        word: String,
+    },
+    /// Report how often maximum entropy and minimax pick different
+    /// guesses, over the candidate sets left after the opener.
+    Disagreement,
+    /// Show the spread of information (entropy, in bits) over every
+    /// opener: the range, mean and a histogram.
+    EntropyDistribution {
+        /// Number of histogram bins.
+        #[clap(long, default_value = "10")]
+        bins: usize,
+    },
+    /// Show how common each letter is in the answers, overall and by
+    /// position.
+    LetterStats {
+        /// Also show how many of those a word covers.
+        word: Option<String>,
+    },
+    /// List the groups of answers that are anagrams of each other, which
+    /// only positional feedback can tell apart.
+    Anagrams,
+    /// Find a small set of guesses which, played together, tell all the
+    /// answers apart, and report any they can't.
+    SeparatingSet,
+    /// List the pairs of answers that, guessing only answers, can only
+    /// be told apart by guessing one of them.
+    AnswerOnlyPairs,
+    /// Find the best pair of openers to play together, whatever the
+    /// first feedback, by expected candidates remaining after both,
+    /// over the answers considered (see --pattern and --metric-answers).
+    BestOpenerPair {
+        /// The second opener is one of this many best single openers.
+        #[clap(long, default_value = "20")]
+        shortlist: usize,
+    },
+}
+
+// Explain where the word lists should be, for a first run without them.
+fn missing_word_list(path: &Path) {
+    eprintln!("Could not find the word list '{}'.", path.display());
+    eprintln!(
+        "By default the word lists are possible_guesses.txt and possible_solutions.txt in '{}'.",
+        words_dir().display()
+    );
+    eprintln!("Run from the directory holding 'words', set WORDLE_WORDS_DIR to the directory");
+    eprintln!("holding the lists, or give their paths with --guesses and --solutions.");
 }
 
 fn main() {
     let opts: Opts = Opts::parse();
 
-    assert!(opts.shard_index < opts.shard_count);
-    let mut s = Scorer::new();
-    s.optimise_guess_order();
+    if let Some(words) = &opts.debug_score {
+        debug_score(opts.rules, &words[0], &words[1]);
+        return;
+    }
 
-    let answer_idxs = s
-        .answers
-        .iter()
-        .enumerate()
-        .map(|(idx, _)| idx)
+    let options = ScorerOptions {
+        open_dictionary: opts.open_dictionary,
+        filter_guess_length: opts.filter_guess_length,
+        rules: opts.rules,
+        cached_guesses: opts.cached_guesses,
+        ..ScorerOptions::default()
+    };
+    let dir = words_dir();
+    let guesses_path = opts.guesses.clone().unwrap_or_else(|| dir.join("possible_guesses.txt"));
+    let solutions_path = opts.solutions.clone().unwrap_or_else(|| dir.join("possible_solutions.txt"));
+    let mut s = Scorer::from_paths_with_options(&guesses_path, &solutions_path, &options).unwrap_or_else(|e| {
+        match &e {
+            WordleError::Io { path, error } if error.kind() == std::io::ErrorKind::NotFound => {
+                missing_word_list(path)
+            }
+            _ => eprintln!("{}", e),
+        }
+        process::exit(EXIT_ERROR);
+    });
+
+    eprintln!("Loaded {}", s.word_list_stats);
+    s.endgame_limit = opts.endgame;
+    s.use_answer_endgame(opts.answer_endgame);
+    s.prefer_input_order = opts.prefer_input_order;
+    s.hard_mode = opts.hard_mode;
+    if s.hard_mode && s.endgame_limit > 0 {
+        eprintln!("Warning: --endgame is ignored in hard mode");
+    }
+    s.log_batch = opts.log_batch;
+
+    if let Some(path) = &opts.block {
+        let num_guesses = s.guesses.len();
+        let kept = s.block_guesses_from(path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(EXIT_ERROR);
+        });
+        for word in kept.iter() {
+            eprintln!("Not blocking '{}', which is a possible answer and so must stay guessable", word);
+        }
+        eprintln!("Blocked {} guesses", num_guesses - s.guesses.len());
+    }
+
+    if let Some(path) = &opts.export_matrix {
+        if let Err(e) = s.write_score_matrix(path) {
+            eprintln!("{}", e);
+            process::exit(EXIT_ERROR);
+        }
+        return;
+    }
+
+    let initial_idxs = match &opts.candidates_file {
+        Some(path) if opts.skip_unknown => {
+            let (candidates, skipped) = s.read_candidates_skipping_unknown(path).unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                process::exit(EXIT_ERROR);
+            });
+            for word in skipped.iter() {
+                eprintln!("Skipping '{}', which is not in the answer list", word);
+            }
+            if !skipped.is_empty() {
+                eprintln!("Skipped {} words in '{}'", skipped.len(), path.display());
+            }
+            candidates
+        }
+        Some(path) => s.read_candidates(path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(EXIT_ERROR);
+        }),
+        None => (0..s.answers.len()).collect(),
+    };
+    let pattern = opts.pattern.as_ref().map(|p| {
+        Pattern::parse(p, &s.alphabet).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(EXIT_ERROR);
+        })
+    });
+    if let Some(pattern) = pattern.as_ref().filter(|p| p.word_len() != s.word_len) {
+        eprintln!("Pattern is {} letters long, but the words have {}", pattern.word_len(), s.word_len);
+        process::exit(EXIT_ERROR);
+    }
+    let answer_idxs = initial_idxs
+        .into_iter()
+        .filter(|idx| pattern.as_ref().is_none_or(|p| p.matches(s.answers[*idx].as_bytes())))
         .collect::<Vec<usize>>();
 
-    let possible = can_solve_noisy(&mut s, DEPTH, &answer_idxs, opts.shard_index, opts.shard_count);
-    if possible {
-        println!("Success with {} guesses!", DEPTH);
-        process::exit(0);
+    if answer_idxs.is_empty() {
+        eprintln!("No possible answers match the given constraints.");
+        process::exit(EXIT_NEGATIVE);
+    }
+    if answer_idxs.len() < s.answers.len() {
+        eprintln!("Narrowed to {} possible answers", answer_idxs.len());
     }
-    println!("Cannot fully determine with {} guesses. Oh well.", DEPTH);
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    let metric_idxs = opts.metric_answers.as_ref().map(|path| {
+        s.read_candidates(path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(EXIT_ERROR);
+        })
+    });
 
-    const A: CharScore = CharScore::Absent;
-    const C: CharScore = CharScore::Correct;
-    const P: CharScore = CharScore::Present;
+    let opener = opts.opener.as_ref().map(|word| {
+        s.guess_index(&word.to_ascii_lowercase()).unwrap_or_else(|| {
+            eprintln!("Opener '{}' is not in the word list", word);
+            process::exit(EXIT_ERROR);
+        })
+    });
+    let opener = if opts.random_opener {
+        let opener = s
+            .random_opener(&answer_idxs, opts.ranking(), &mut Rng::new(session_seed(opts.seed)))
+            .unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                process::exit(EXIT_ERROR);
+            });
+        eprintln!("Random opener: {}", s.guesses[opener]);
+        Some(opener)
+    } else {
+        opener
+    };
 
-    fn check(guess: &str, answer: &str, score: &[CharScore]) {
-        assert_eq!(
-            score_wordle(guess.as_bytes(), answer.as_bytes()),
-            encode_score(score.iter().cloned())
-        );
+    if let Some(boards) = opts.boards {
+        multi_board(&s, &opts, opener, &answer_idxs, boards);
+        return;
+    }
+
+    if let (Some(opener), true) = (opener, opts.show_worst_bucket) {
+        show_worst_buckets(&s, opener, opts.top_buckets.unwrap_or(1), !opts.sizes_only, opts.max_candidates_print);
+        return;
+    }
+
+    if let (Some(opener), true) = (opener, opts.worst_case) {
+        worst_case(&s, opts.ranking(), opener, opts.progress_every);
+        return;
+    }
+
+    if let Some(answer) = &opts.solve {
+        solve_one(&s, opts.ranking(), opener, answer, opts.share);
+        return;
+    }
+
+    if let Some(target) = &opts.target {
+        play_target(&s, opts.ranking(), opener, &answer_idxs, target);
+        return;
+    }
+
+    #[cfg(feature = "http")]
+    if let Some(url) = &opts.http_url {
+        play_http(&s, opts.ranking(), opener, &answer_idxs, url);
+        return;
     }
 
-    #[test]
-    fn test_simple_green() {
-        check("weary", "wills", &[C, A, A, A, A]);
+    if let Some(p) = opts.noise {
+        noise(&s, opts.ranking(), opener, &answer_idxs, p, opts.seed.unwrap_or(0));
+        return;
     }
 
-    #[test]
-    fn test_simple_yellow() {
-        check("pilot", "leaks", &[A, A, P, A, A]);
+    if opts.cheat_sheet {
+        let opener = opener.unwrap_or_else(|| s.first_guess(&answer_idxs, opts.ranking(), opts.human));
+        cheat_sheet(&s, opts.ranking(), opener, &answer_idxs);
+        return;
     }
 
-    #[test]
-    fn test_double_yellow() {
-        check("kazoo", "tools", &[A, A, A, P, P]);
+    if opts.absurdle {
+        absurdle(&s, opts.ranking(), opener, &answer_idxs);
+        return;
     }
 
-    #[test]
-    fn test_green_overrides_yellow() {
-        // Letters are 'used up' by exact matches.
-        check("loose", "chore", &[A, A, C, A, C]);
+    if opts.self_solve {
+        let second = opts.second.as_ref().map(|word| {
+            s.guess_index(&word.to_ascii_lowercase()).unwrap_or_else(|| {
+                eprintln!("Second guess '{}' is not in the word list", word);
+                process::exit(EXIT_ERROR);
+            })
+        });
+        self_solve(&s, &opts, opener, second, &answer_idxs);
+        return;
+    }
+
+    if opts.server {
+        let stdin = std::io::stdin();
+        if let Err(e) = serve(&s, opts.ranking(), answer_idxs, stdin.lock(), std::io::stdout()) {
+            eprintln!("Error in server mode: {}", e);
+            process::exit(EXIT_ERROR);
+        }
+        return;
+    }
+
+    if opts.practice {
+        let answer = Rng::new(session_seed(opts.seed)).below(answer_idxs.len());
+        let answer = answer_idxs[answer];
+        let stdin = std::io::stdin();
+        let result = practice(&s, opts.ranking(), opener, answer_idxs, answer, stdin.lock(), std::io::stdout());
+        if let Err(e) = result {
+            eprintln!("Error in practice mode: {}", e);
+            process::exit(EXIT_ERROR);
+        }
+        return;
     }
 
-    #[test]
-    fn test_yellow_overrides_yellow() {
-        // Letters are 'used up' by inexact matches, too.
-        // So, only one 'O' matches.
-        check("spoon", "coats", &[P, A, P, A, A]);
+    if opts.interactive {
+        interactive::run(
+            &s,
+            opts.ranking(),
+            opener,
+            answer_idxs,
+            opts.max_candidates_print,
+            opts.explain,
+            opts.assist,
+        );
+        return;
+    }
+
+    if let (Some(opener), true) = (opener, opts.two_count) {
+        two_count(&s, opener, &answer_idxs);
+        return;
     }
 
-    #[test]
-    fn test_success() {
-        check("prize", "prize", &[C, C, C, C, C]);
+    match &opts.command {
+        Some(Command::Next { history }) => next(&s, &opts, &answer_idxs, history),
+        Some(Command::CheckFeedback { guess, feedback }) => check_feedback(&s, guess, feedback),
+        Some(Command::ExistsWithin { max }) => exists_within(&s, &answer_idxs, *max),
+        Some(Command::WinProbability { budget }) => println!(
+            "{:.2}% chance of solving within {} guesses",
+            100.0 * s.win_probability(&answer_idxs, *budget),
+            budget
+        ),
+        Some(Command::Regret { optimal_budget, checkpoint }) => regret(
+            &s,
+            opts.ranking(),
+            &answer_idxs,
+            *optimal_budget,
+            checkpoint.as_deref(),
+        ),
+        Some(Command::Anagrams) => anagrams(&s, opts.max_candidates_print),
+        Some(Command::SeparatingSet) => separating_set(&s),
+        Some(Command::AnswerOnlyPairs) => answer_only_pairs(&s, opts.max_candidates_print),
+        Some(Command::Disagreement) => disagreement(&s, opener, &answer_idxs),
+        Some(Command::Openers { top, common }) => {
+            let metric_idxs = metric_idxs.as_deref().unwrap_or(&answer_idxs);
+            openers(&s, opts.ranking(), metric_idxs, *top, *common)
+        }
+        Some(Command::OpenerRanks { word }) => opener_ranks(&s, word),
+        Some(Command::EntropyDistribution { bins }) => entropy_distribution(&s, &answer_idxs, *bins),
+        Some(Command::LetterStats { word }) => letter_stats(&s, &answer_idxs, word.as_deref()),
+        Some(Command::BestOpenerPair { shortlist }) => {
+            match s.best_opener_pair(metric_idxs.as_deref().unwrap_or(&answer_idxs), *shortlist) {
+                Some((first, second, expected)) => {
+                    println!("{} {}: {:.4} expected remaining", first, second, expected)
+                }
+                None => {
+                    eprintln!("Need at least two guesses and a shortlist of at least one");
+                    process::exit(EXIT_ERROR);
+                }
+            }
+        }
+        Some(Command::PairDistribution { first, second }) => {
+            pair_distribution(&s, &answer_idxs, first, second)
+        }
+        Some(Command::OpenerDrift { removed }) => opener_drift(&s, &answer_idxs, removed),
+        None => search(&mut s, &opts, &answer_idxs, metric_idxs.as_deref()),
     }
 }
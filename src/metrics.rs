@@ -0,0 +1,297 @@
+//
+// Per-guess bucket sizes over a set of answers, kept up to date as
+// answers are removed, so that the guess order can be re-optimised
+// (e.g. after excluding past answers) without rescoring everything.
+//
+
+use std::collections::HashMap;
+
+use crate::{BucketId, Rank, Ranking, Scorer, WordleError, MAX_DENSE_BUCKETS};
+
+// The number of answers in each of a guess's buckets: an array with
+// room for every score, or for long words, a map of the ones seen.
+#[derive(Clone)]
+enum BucketCounts {
+    Dense(Vec<u16>),
+    Sparse(HashMap<BucketId, u16>),
+}
+
+impl BucketCounts {
+    fn new(num_buckets: usize) -> BucketCounts {
+        if num_buckets <= MAX_DENSE_BUCKETS {
+            BucketCounts::Dense(vec![0; num_buckets])
+        } else {
+            BucketCounts::Sparse(HashMap::new())
+        }
+    }
+
+    fn get_mut(&mut self, bucket: BucketId) -> &mut u16 {
+        match self {
+            BucketCounts::Dense(counts) => &mut counts[bucket as usize],
+            BucketCounts::Sparse(counts) => counts.entry(bucket).or_insert(0),
+        }
+    }
+
+    // The counts, including any empty buckets.
+    fn values(&self) -> Box<dyn Iterator<Item = u16> + '_> {
+        match self {
+            BucketCounts::Dense(counts) => Box::new(counts.iter().copied()),
+            BucketCounts::Sparse(counts) => Box::new(counts.values().copied()),
+        }
+    }
+}
+
+pub struct GuessMetrics {
+    // For each guess, the number of answers in each bucket.
+    counts: Vec<BucketCounts>,
+    // For each guess, the sum of the squared bucket sizes.
+    sum_of_squares: Vec<usize>,
+    num_answers: usize,
+}
+
+// Where one guess stands among all of them by a metric.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetricRank {
+    pub value: f64,
+    // 1 + the number of guesses strictly better.
+    pub rank: usize,
+    // How many other guesses are just as good.
+    pub ties: usize,
+}
+
+impl MetricRank {
+    // Smaller values are better.
+    fn new(values: &[f64], guess: usize) -> MetricRank {
+        let value = values[guess];
+        MetricRank {
+            value,
+            rank: 1 + values.iter().filter(|v| **v < value).count(),
+            ties: values.iter().filter(|v| **v == value).count() - 1,
+        }
+    }
+
+    // Could a tie-break make this the best?
+    pub fn can_be_best(&self) -> bool {
+        self.rank == 1
+    }
+}
+
+// How an opener ranks by each metric, over all the answers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OpenerRanks {
+    pub num_guesses: usize,
+    pub worst_case: MetricRank,
+    // Ranked by most information first, i.e. by negated entropy.
+    pub entropy: MetricRank,
+    pub expected_remaining: MetricRank,
+    // Ranked by most buckets first.
+    pub num_buckets: MetricRank,
+}
+
+impl GuessMetrics {
+    pub fn num_answers(&self) -> usize {
+        self.num_answers
+    }
+
+    // As Scorer::find_greedy_worst_case.
+    pub fn worst_case(&self, guess: usize) -> usize {
+        self.counts[guess].values().max().unwrap_or(0) as usize
+    }
+
+    // As Scorer::expected_remaining.
+    pub fn expected_remaining(&self, guess: usize) -> f64 {
+        if self.num_answers == 0 {
+            return 0.0;
+        }
+        self.sum_of_squares[guess] as f64 / self.num_answers as f64
+    }
+
+    // As Scorer::bucket_count.
+    pub fn bucket_count(&self, guess: usize) -> usize {
+        self.counts[guess].values().filter(|c| *c > 0).count()
+    }
+
+    // As Scorer::rank_guess, bar ResidualWorstCase.
+    pub fn rank(&self, ranking: Ranking, guess: usize) -> Rank {
+        match ranking {
+            Ranking::WorstCase => (self.worst_case(guess) as f64, 0),
+            Ranking::ExpectedRemaining => (self.expected_remaining(guess), 0),
+            Ranking::MaximizePartitions => (-(self.bucket_count(guess) as f64), self.worst_case(guess)),
+            // Needs more than the bucket sizes, so approximated by the
+            // worst case, which it refines.
+            Ranking::ResidualWorstCase => (self.worst_case(guess) as f64, 0),
+        }
+    }
+
+    // The best guess under the ranking, the first if several tie.
+    pub fn best_guess(&self, ranking: Ranking) -> usize {
+        (0..self.counts.len())
+            .min_by(|a, b| self.rank(ranking, *a).partial_cmp(&self.rank(ranking, *b)).unwrap())
+            .unwrap()
+    }
+
+    // Take answers out of every guess's buckets. That's one update per
+    // guess for each answer removed, rather than rebucketing all the
+    // answers that are left.
+    pub fn remove_answers(&mut self, s: &Scorer, removed: &[usize]) {
+        for (guess, counts) in self.counts.iter_mut().enumerate() {
+            for answer in removed.iter() {
+                let count = counts.get_mut(s.score(guess, *answer));
+                assert!(*count > 0, "Answer {} removed twice", answer);
+                self.sum_of_squares[guess] -= 2 * *count as usize - 1;
+                *count -= 1;
+            }
+        }
+        self.num_answers -= removed.len();
+    }
+}
+
+impl Scorer {
+    // Bucket the answers for every guess. The counts are u16s, so that
+    // many answers at most.
+    pub fn guess_metrics(&self, answers: &[usize]) -> Result<GuessMetrics, WordleError> {
+        if answers.len() > u16::MAX as usize {
+            return Err(WordleError::InvalidInput(format!(
+                "Too many answers to bucket: {}, at most {}",
+                answers.len(),
+                u16::MAX
+            )));
+        }
+        let counts = (0..self.guesses.len())
+            .map(|guess| {
+                let row = self.scores(guess);
+                let mut counts = BucketCounts::new(self.num_buckets());
+                for answer in answers.iter() {
+                    *counts.get_mut(row[*answer]) += 1;
+                }
+                counts
+            })
+            .collect::<Vec<_>>();
+        let sum_of_squares = counts
+            .iter()
+            .map(|c| c.values().map(|n| n as usize * n as usize).sum())
+            .collect();
+        Ok(GuessMetrics {
+            counts,
+            sum_of_squares,
+            num_answers: answers.len(),
+        })
+    }
+
+    // How the word ranks as an opener by each metric, to see whether
+    // it's ever the best.
+    pub fn opener_ranks(&self, word: &str) -> Result<OpenerRanks, WordleError> {
+        let guess = self.guess_index(word).ok_or_else(|| WordleError::UnknownGuess {
+            word: word.to_string(),
+        })?;
+        let answers = (0..self.answers.len()).collect::<Vec<_>>();
+        let metrics = self.guess_metrics(&answers)?;
+        let values = |f: &dyn Fn(usize) -> f64| (0..self.guesses.len()).map(f).collect::<Vec<_>>();
+        let entropies = self.entropies(&answers).iter().map(|e| -e).collect::<Vec<_>>();
+        Ok(OpenerRanks {
+            num_guesses: self.guesses.len(),
+            worst_case: MetricRank::new(&values(&|g| metrics.worst_case(g) as f64), guess),
+            entropy: MetricRank::new(&entropies, guess),
+            expected_remaining: MetricRank::new(&values(&|g| metrics.expected_remaining(g)), guess),
+            num_buckets: MetricRank::new(&values(&|g| -(metrics.bucket_count(g) as f64)), guess),
+        })
+    }
+
+    // How the best opener by expected remaining changes as answers are
+    // removed one by one, in the given order: the opener and its
+    // expected remaining before any are removed, then after each.
+    pub fn opener_drift(&self, answers: &[usize], removed: &[usize]) -> Result<Vec<(usize, f64)>, WordleError> {
+        let mut metrics = self.guess_metrics(answers)?;
+        let best = |metrics: &GuessMetrics| {
+            let guess = metrics.best_guess(Ranking::ExpectedRemaining);
+            (guess, metrics.expected_remaining(guess))
+        };
+        let mut drift = vec![best(&metrics)];
+        for answer in removed.iter() {
+            metrics.remove_answers(self, &[*answer]);
+            drift.push(best(&metrics));
+        }
+        Ok(drift)
+    }
+
+    // As optimise_guess_order, using the given metrics rather than
+    // recalculating them. The metrics are reordered to match.
+    pub fn optimise_guess_order_with(&mut self, ranking: Ranking, metrics: &mut GuessMetrics) {
+        let ranks = (0..self.guesses.len())
+            .map(|guess| metrics.rank(ranking, guess))
+            .collect::<Vec<_>>();
+        let (order, _) = self.apply_guess_ranks(ranking, &ranks);
+        metrics.counts = order.iter().map(|idx| metrics.counts[*idx].clone()).collect();
+        metrics.sum_of_squares = order.iter().map(|idx| metrics.sum_of_squares[*idx]).collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::small_scorer;
+
+    #[test]
+    fn test_opener_ranks() {
+        let s = small_scorer(&["zzzzb", "zzzbc"], &["aaaaa", "aaaab", "aaaac"]);
+
+        // "zzzzb" and each answer split off one answer, "zzzbc" all three.
+        let ranks = s.opener_ranks("zzzzb").unwrap();
+        assert_eq!(ranks.num_guesses, 5);
+        assert_eq!(ranks.worst_case, MetricRank { value: 2.0, rank: 2, ties: 3 });
+        assert_eq!(ranks.num_buckets, MetricRank { value: -2.0, rank: 2, ties: 3 });
+        assert!(!ranks.expected_remaining.can_be_best());
+        assert_eq!(ranks.entropy.rank, 2);
+
+        let ranks = s.opener_ranks("zzzbc").unwrap();
+        assert!(ranks.worst_case.can_be_best());
+        assert_eq!(ranks.worst_case.ties, 0);
+        assert!(ranks.entropy.can_be_best());
+
+        assert!(s.opener_ranks("qqqqq").is_err());
+    }
+
+    #[test]
+    fn test_opener_drift() {
+        let s = small_scorer(&["zzzzb", "zzzbc"], &["aaaaa", "aaaab", "aaaac"]);
+
+        // "zzzbc" splits all three, but once "aaaac" is gone "zzzzb" does
+        // as well, and comes first.
+        let drift = s.opener_drift(&[0, 1, 2], &[2, 1]).unwrap();
+        assert_eq!(drift.iter().map(|(guess, _)| *guess).collect::<Vec<_>>(), vec![1, 0, 0]);
+        assert_eq!(drift.iter().map(|(_, expected)| *expected).collect::<Vec<_>>(), vec![1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_remove_answers() {
+        let mut s = small_scorer(&["zzzzb", "zzzbc", "aabcd"], &["aaaaa", "aaaab", "aaaac", "aaaad"]);
+        let rankings = [Ranking::WorstCase, Ranking::ExpectedRemaining, Ranking::MaximizePartitions];
+
+        // Incrementally updated metrics match those calculated afresh.
+        let mut metrics = s.guess_metrics(&[0, 1, 2, 3]).unwrap();
+        metrics.remove_answers(&s, &[1, 3]);
+        assert_eq!(metrics.num_answers(), 2);
+        for guess in 0..s.guesses.len() {
+            for ranking in rankings.iter() {
+                assert_eq!(metrics.rank(*ranking, guess), s.rank_guess(*ranking, guess, &[0, 2]));
+            }
+        }
+
+        // And still match once the guesses are reordered.
+        s.optimise_guess_order_with(Ranking::WorstCase, &mut metrics);
+        for guess in 0..s.guesses.len() {
+            assert_eq!(metrics.rank(Ranking::WorstCase, guess), s.rank_guess(Ranking::WorstCase, guess, &[0, 2]));
+        }
+
+        // Likewise for words long enough to count their buckets sparsely.
+        let s = small_scorer(&["zzzzzzb", "zzzzzbc"], &["aaaaaaa", "aaaaaab", "aaaaaac", "aaaaaad"]);
+        assert!(s.num_buckets() > MAX_DENSE_BUCKETS);
+        let mut metrics = s.guess_metrics(&[0, 1, 2, 3]).unwrap();
+        metrics.remove_answers(&s, &[1, 3]);
+        for guess in 0..s.guesses.len() {
+            for ranking in rankings.iter() {
+                assert_eq!(metrics.rank(*ranking, guess), s.rank_guess(*ranking, guess, &[0, 2]));
+            }
+        }
+    }
+}
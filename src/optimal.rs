@@ -0,0 +1,463 @@
+//
+// Exact solver, finding the strategy that minimises the total (and so
+// average) number of guesses over the candidate answers.
+//
+// This is a branch-and-bound search over every guess at every step, so
+// it's only practical for fairly small candidate sets. For bigger ones
+// the memo table can be checkpointed to disk, so that the search can be
+// picked up again in a later run.
+//
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::{candidate_signature, word_list_signature, Scorer, WordleError};
+
+// Candidate sets to visit between checkpoints.
+const CHECKPOINT_INTERVAL: usize = 10_000;
+
+pub struct OptimalSolver<'a> {
+    s: &'a Scorer,
+    // For each candidate set solved so far, the lowest total number of
+    // guesses and the first guess that achieves it.
+    memo: HashMap<Vec<usize>, (usize, usize)>,
+    // Candidate sets to search before settling for the best strategy
+    // found so far, if limited.
+    budget: Option<usize>,
+    nodes: usize,
+    // Results loaded from a checkpoint, keyed by candidate_signature.
+    resumed: HashMap<u64, (usize, usize)>,
+    // Where to save checkpoints, if anywhere, and the node count at the
+    // last one.
+    checkpoint: Option<PathBuf>,
+    last_checkpoint: usize,
+}
+
+impl<'a> OptimalSolver<'a> {
+    pub fn new(s: &'a Scorer) -> OptimalSolver<'a> {
+        OptimalSolver {
+            s,
+            memo: HashMap::new(),
+            budget: None,
+            nodes: 0,
+            resumed: HashMap::new(),
+            checkpoint: None,
+            last_checkpoint: 0,
+        }
+    }
+
+    // A solver that gives up searching after visiting 'budget' candidate
+    // sets. After that, each candidate set takes the best guess found so
+    // far, or failing that the most promising one, so results may not
+    // be optimal: see is_proven.
+    pub fn with_budget(s: &'a Scorer, budget: usize) -> OptimalSolver<'a> {
+        OptimalSolver {
+            budget: Some(budget),
+            ..OptimalSolver::new(s)
+        }
+    }
+
+    fn out_of_budget(&self) -> bool {
+        self.budget.is_some_and(|budget| self.nodes >= budget)
+    }
+
+    // Are all the results so far known to be optimal?
+    pub fn is_proven(&self) -> bool {
+        !self.out_of_budget()
+    }
+
+    // Returns the lowest total number of guesses needed to solve each of
+    // the (sorted) candidates, and the first guess of a strategy that
+    // achieves it.
+    pub fn solve(&mut self, candidates: &[usize]) -> (usize, usize) {
+        let s = self.s;
+        if let [answer] = candidates {
+            return (1, s.guess_index(&s.answers[*answer]).unwrap());
+        }
+        if let Some(result) = self.memo.get(candidates) {
+            return *result;
+        }
+        if !self.resumed.is_empty() {
+            if let Some(result) = self.resumed.get(&candidate_signature(candidates)) {
+                return *result;
+            }
+        }
+        self.nodes += 1;
+
+        // Every candidate costs this guess, and all but at most one of
+        // each bucket needs at least two more. Skip guesses that don't
+        // split the candidates at all, and try the most promising first.
+        let mut options = (0..s.guesses.len())
+            .filter_map(|guess| {
+                let buckets = s.bucket_answers(guess, candidates);
+                if buckets.len() == 1 && s.answers[buckets[0][0]] != s.guesses[guess] {
+                    return None;
+                }
+                let bound = candidates.len()
+                    + buckets
+                        .iter()
+                        .filter(|b| !is_solved_by(s, guess, b))
+                        .map(|b| 2 * b.len() - 1)
+                        .sum::<usize>();
+                Some((bound, guess, buckets))
+            })
+            .collect::<Vec<_>>();
+        options.sort_by_key(|(bound, guess, _)| (*bound, *guess));
+
+        let mut best = (usize::MAX, 0);
+        for (bound, guess, buckets) in options {
+            if bound >= best.0 || (self.out_of_budget() && best.0 != usize::MAX) {
+                break;
+            }
+            let mut total = candidates.len();
+            for bucket in buckets.iter().filter(|b| !is_solved_by(s, guess, b)) {
+                total += self.solve(bucket).0;
+                if total >= best.0 {
+                    break;
+                }
+            }
+            if total < best.0 {
+                best = (total, guess);
+            }
+        }
+
+        // Only remember results that are known to be the best.
+        if !self.out_of_budget() {
+            self.memo.insert(candidates.to_vec(), best);
+        }
+        if self.nodes - self.last_checkpoint >= CHECKPOINT_INTERVAL {
+            if let Some(path) = self.checkpoint.clone() {
+                self.last_checkpoint = self.nodes;
+                if let Err(e) = self.save_checkpoint(&path) {
+                    eprintln!("Warning: checkpoint failed: {}", e);
+                }
+            }
+        }
+        best
+    }
+
+    // Load the results saved in a checkpoint, if it exists, and save
+    // checkpoints there as the search goes on. A checkpoint made with
+    // other word lists or rules is rejected, going by its header.
+    pub fn resume_from(&mut self, path: impl AsRef<Path>) -> Result<(), WordleError> {
+        let path = path.as_ref();
+        match std::fs::read_to_string(path) {
+            Ok(text) => self.parse_checkpoint(&text, path)?,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+            Err(error) => {
+                return Err(WordleError::Io {
+                    path: path.to_path_buf(),
+                    error,
+                })
+            }
+        }
+        self.checkpoint = Some(path.to_path_buf());
+        Ok(())
+    }
+
+    // The first line of a checkpoint, identifying what it was made with:
+    // the number of answers and a hash of them in order (as candidate
+    // sets are keyed by answer index), a hash of the guesses (in any
+    // order, as they're saved as words) and the rules.
+    fn checkpoint_header(&self) -> String {
+        let s = self.s;
+        let mut guesses = s.guesses.iter().map(|g| g.as_str()).collect::<Vec<_>>();
+        guesses.sort_unstable();
+        format!(
+            "checkpoint {} {:016x} {:016x} {}",
+            s.answers.len(),
+            word_list_signature(s.answers.iter().map(|a| a.as_str())),
+            word_list_signature(guesses.into_iter()),
+            s.rules
+        )
+    }
+
+    // After the header, each line is a candidate set's signature (in
+    // hex), the total guesses and the first guess.
+    fn parse_checkpoint(&mut self, text: &str, path: &Path) -> Result<(), WordleError> {
+        let s = self.s;
+        let mut lines = text.lines().filter(|l| !l.is_empty());
+        if lines.next() != Some(self.checkpoint_header().as_str()) {
+            return Err(WordleError::InvalidInput(format!(
+                "Checkpoint '{}' was made with other word lists or rules",
+                path.display()
+            )));
+        }
+        for line in lines {
+            let bad_line = || {
+                WordleError::InvalidInput(format!("Bad line '{}' in checkpoint '{}'", line, path.display()))
+            };
+            let fields = line.split(' ').collect::<Vec<_>>();
+            if fields.len() != 3 {
+                return Err(bad_line());
+            }
+            let signature = u64::from_str_radix(fields[0], 16).map_err(|_| bad_line())?;
+            let total = fields[1].parse::<usize>().map_err(|_| bad_line())?;
+            let guess = s.guess_index(fields[2]).ok_or_else(|| WordleError::UnknownGuess {
+                word: fields[2].to_string(),
+            })?;
+            self.resumed.insert(signature, (total, guess));
+        }
+        Ok(())
+    }
+
+    // Save everything known so far, including anything resumed. The
+    // guesses are saved as words, so reordering them doesn't matter.
+    pub fn save_checkpoint(&self, path: impl AsRef<Path>) -> Result<(), WordleError> {
+        let path = path.as_ref();
+        let s = self.s;
+        let mut entries = self.resumed.clone();
+        for (candidates, result) in self.memo.iter() {
+            entries.insert(candidate_signature(candidates), *result);
+        }
+        let mut entries = entries.into_iter().collect::<Vec<_>>();
+        entries.sort_unstable();
+        let mut text = format!("{}\n", self.checkpoint_header());
+        text.extend(
+            entries
+                .iter()
+                .map(|(signature, (total, guess))| format!("{:016x} {} {}\n", signature, total, s.guesses[*guess])),
+        );
+
+        // Write it somewhere else first, so an interrupted write doesn't
+        // lose the last checkpoint.
+        let io_error = |error| WordleError::Io {
+            path: path.to_path_buf(),
+            error,
+        };
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        std::fs::write(&tmp_path, text).map_err(io_error)?;
+        std::fs::rename(&tmp_path, path).map_err(io_error)
+    }
+
+    // Number of guesses the optimal strategy takes to find the target.
+    pub fn guesses_for(&mut self, target: usize, candidates: &[usize]) -> usize {
+        let s = self.s;
+        let mut candidates = candidates.to_vec();
+        let mut num_guesses = 0;
+        loop {
+            let (_, guess) = self.solve(&candidates);
+            num_guesses += 1;
+            if s.guesses[guess] == s.answers[target] {
+                return num_guesses;
+            }
+            candidates = s
+                .filter_candidates_checked(guess, s.score(guess, target), &candidates, Some(target))
+                .unwrap();
+        }
+    }
+}
+
+// The best chance of solving within the budget, memoised on the
+// (sorted) candidates and budget. See Scorer::win_probability.
+fn win_probability(
+    s: &Scorer,
+    candidates: &[usize],
+    budget: usize,
+    memo: &mut HashMap<(Vec<usize>, usize), f64>,
+) -> f64 {
+    match (candidates.len(), budget) {
+        (0, _) | (_, 0) => return 0.0,
+        (1, _) => return 1.0,
+        // Guess a candidate, and hope.
+        (n, 1) => return 1.0 / n as f64,
+        // Every bucket left after the guess gets one guess at it, so
+        // wins once: the more buckets, the better.
+        (n, 2) => {
+            let most = (0..s.guesses.len()).map(|guess| s.bucket_count(guess, candidates)).max().unwrap();
+            return most as f64 / n as f64;
+        }
+        _ => (),
+    }
+    if let Some(p) = memo.get(&(candidates.to_vec(), budget)) {
+        return *p;
+    }
+
+    let mut best = 0.0;
+    for guess in 0..s.guesses.len() {
+        let buckets = s.bucket_answers(guess, candidates);
+        // A guess that splits nothing just wastes one.
+        if buckets.len() == 1 && !is_solved_by(s, guess, &buckets[0]) {
+            continue;
+        }
+        let wins = buckets
+            .iter()
+            .map(|b| {
+                if is_solved_by(s, guess, b) {
+                    1.0
+                } else {
+                    b.len() as f64 * win_probability(s, b, budget - 1, memo)
+                }
+            })
+            .sum::<f64>();
+        best = f64::max(best, wins / candidates.len() as f64);
+        if best >= 1.0 {
+            break;
+        }
+    }
+    memo.insert((candidates.to_vec(), budget), best);
+    best
+}
+
+// Is the bucket just the guess itself, so needs no more guesses?
+fn is_solved_by(s: &Scorer, guess: usize, bucket: &[usize]) -> bool {
+    bucket.len() == 1 && s.answers[bucket[0]] == s.guesses[guess]
+}
+
+impl Scorer {
+    // The lowest total number of guesses to solve each candidate, and a
+    // first guess that achieves it.
+    pub fn solve_optimal(&self, candidates: &[usize]) -> (usize, usize) {
+        OptimalSolver::new(self).solve(candidates)
+    }
+
+    // The average number of guesses to solve each answer after opening
+    // with the given guess, playing optimally from there: the precise
+    // measure of how good an opener is, which the rankings approximate.
+    // Only practical where each of its buckets is small enough for
+    // solve_optimal.
+    pub fn optimal_average_for_opener(&self, opener: usize) -> f64 {
+        let answers = (0..self.answers.len()).collect::<Vec<_>>();
+        let mut solver = OptimalSolver::new(self);
+        let total = answers.len()
+            + self
+                .bucket_answers(opener, &answers)
+                .iter()
+                .filter(|b| !is_solved_by(self, opener, b))
+                .map(|b| solver.solve(b).0)
+                .sum::<usize>();
+        total as f64 / answers.len() as f64
+    }
+
+    // The chance of solving within 'budget' guesses, with each of the
+    // (sorted) candidates equally likely, playing to maximise it: the
+    // best strategy for keeping a streak going, which may not have the
+    // best average. Only practical for small candidate sets.
+    pub fn win_probability(&self, candidates: &[usize], budget: usize) -> f64 {
+        win_probability(self, candidates, budget, &mut HashMap::new())
+    }
+
+    // As solve_optimal, but giving up on the search after visiting
+    // 'budget' candidate sets. Also returns whether the result is
+    // proven optimal.
+    pub fn solve_optimal_within(&self, candidates: &[usize], budget: usize) -> (usize, usize, bool) {
+        let mut solver = OptimalSolver::with_budget(self, budget);
+        let (total, guess) = solver.solve(candidates);
+        (total, guess, solver.is_proven())
+    }
+
+    // As solve_optimal, resuming from the checkpoint file if it exists,
+    // and saving to it as the search goes on and once it's done.
+    pub fn solve_optimal_resumable(
+        &self,
+        candidates: &[usize],
+        checkpoint: impl AsRef<Path>,
+    ) -> Result<(usize, usize), WordleError> {
+        let mut solver = OptimalSolver::new(self);
+        solver.resume_from(&checkpoint)?;
+        let result = solver.solve(candidates);
+        solver.save_checkpoint(&checkpoint)?;
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::small_scorer;
+    use crate::{Ranking, ScoringRules};
+
+    #[test]
+    fn test_solve_optimal() {
+        let s = small_scorer(&["zzzbc"], &["aaaaa", "aaaab", "aaaac"]);
+
+        // Guessing an answer: 1 + 2 + 3 (the other two are
+        // indistinguishable). Guessing "zzzbc": 2 + 2 + 2.
+        assert_eq!(s.solve_optimal(&[0, 1, 2]), (6, 0));
+        // With two left, guess one of them: 1 + 2.
+        assert_eq!(s.solve_optimal(&[0, 2]).0, 3);
+
+        let mut solver = OptimalSolver::new(&s);
+        for target in 0..3 {
+            assert_eq!(solver.guesses_for(target, &[0, 1, 2]), 2);
+            assert_eq!(s.guess_greedily(target, &[0, 1, 2], Ranking::WorstCase), 2);
+        }
+    }
+
+    #[test]
+    fn test_optimal_average_for_opener() {
+        let s = small_scorer(&["zzzzb", "zzzbc"], &["aaaaa", "aaaab", "aaaac"]);
+
+        // "zzzbc" leaves each answer alone: 2 + 2 + 2.
+        assert_eq!(s.optimal_average_for_opener(1), 2.0);
+        // "zzzzb" leaves a pair: 2 + 2 + 3.
+        assert_eq!(s.optimal_average_for_opener(0), 7.0 / 3.0);
+        // Opening with an answer solves that one: 1 + 2 + 3.
+        assert_eq!(s.optimal_average_for_opener(2), 2.0);
+    }
+
+    #[test]
+    fn test_win_probability() {
+        let s = small_scorer(&["zzzzb"], &["aaaaa", "aaaab", "aaaac"]);
+
+        assert_eq!(s.win_probability(&[0, 1, 2], 0), 0.0);
+        assert_eq!(s.win_probability(&[0, 1, 2], 1), 1.0 / 3.0);
+        // Nothing splits all three, so one of a pair is left to chance.
+        assert_eq!(s.win_probability(&[0, 1, 2], 2), 2.0 / 3.0);
+        assert_eq!(s.win_probability(&[0, 1, 2], 3), 1.0);
+        assert_eq!(s.win_probability(&[1], 1), 1.0);
+
+        let s = small_scorer(&["zzzbc"], &["aaaaa", "aaaab", "aaaac", "aaaad"]);
+        // "zzzbc" leaves "aaaaa" and "aaaad" together.
+        assert_eq!(s.win_probability(&[0, 1, 2, 3], 2), 0.75);
+        assert_eq!(s.win_probability(&[0, 1, 2, 3], 3), 1.0);
+    }
+
+    #[test]
+    fn test_solve_optimal_within() {
+        let s = small_scorer(&["zzzzb", "zzzbc", "zzbcd"], &["aaaaa", "aaaab", "aaaac", "aaaad"]);
+        let all = [0, 1, 2, 3];
+
+        let (total, _) = s.solve_optimal(&all);
+        assert_eq!(s.solve_optimal_within(&all, 1000), (total, s.solve_optimal(&all).1, true));
+
+        // With hardly any budget, there's still a complete strategy,
+        // just not a proven one.
+        let (partial, _, proven) = s.solve_optimal_within(&all, 1);
+        assert!(!proven);
+        assert!(partial >= total);
+    }
+
+    #[test]
+    fn test_solve_optimal_resumable() {
+        let s = small_scorer(&["zzzzb", "zzzbc", "zzbcd"], &["aaaaa", "aaaab", "aaaac", "aaaad"]);
+        let path = std::env::temp_dir().join(format!("wordle-checkpoint-{}.txt", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let all = [0, 1, 2, 3];
+
+        let expected = s.solve_optimal(&all);
+        assert_eq!(s.solve_optimal_resumable(&all, &path).unwrap(), expected);
+
+        // Resuming finds the answer without searching again.
+        let mut solver = OptimalSolver::new(&s);
+        solver.resume_from(&path).unwrap();
+        assert_eq!(solver.solve(&all), expected);
+        assert_eq!(solver.nodes, 0);
+
+        // Resuming with another answer list, even of the same size, or
+        // other rules, fails rather than reusing the wrong results.
+        let other = small_scorer(&["zzzzb", "zzzbc", "zzbcd"], &["aaaaa", "aaaab", "aaaac", "aaaae"]);
+        assert!(matches!(OptimalSolver::new(&other).resume_from(&path), Err(WordleError::InvalidInput(_))));
+        let mut other = small_scorer(&["zzzzb", "zzzbc", "zzbcd"], &["aaaaa", "aaaab", "aaaac", "aaaad"]);
+        other.rules = ScoringRules::UnlimitedYellows;
+        assert!(matches!(OptimalSolver::new(&other).resume_from(&path), Err(WordleError::InvalidInput(_))));
+
+        std::fs::write(&path, "not a checkpoint\n").unwrap();
+        assert!(matches!(
+            s.solve_optimal_resumable(&all, &path),
+            Err(WordleError::InvalidInput(_))
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
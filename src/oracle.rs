@@ -0,0 +1,259 @@
+//
+// Sources of feedback for guesses: a known answer, a player typing it
+// in, an adversary, or a real game somewhere else. Feedback can also be
+// made wrong on purpose, to see how the solver copes.
+//
+
+use std::io::{BufRead, Write};
+
+use crate::{
+    decode_score, encode_score, parse_score, score_with_rules, BucketId, CharScore, Scorer, ScoringRules, WordleError,
+};
+
+pub trait Oracle {
+    // The feedback for a guess, encoded.
+    fn score(&mut self, guess: &str) -> Result<BucketId, WordleError>;
+}
+
+// Scores guesses locally against an answer the caller knows.
+pub struct KnownAnswer {
+    answer: String,
+    rules: ScoringRules,
+}
+
+impl KnownAnswer {
+    pub fn new(answer: &str, rules: ScoringRules) -> KnownAnswer {
+        KnownAnswer {
+            answer: answer.to_string(),
+            rules,
+        }
+    }
+}
+
+impl Oracle for KnownAnswer {
+    fn score(&mut self, guess: &str) -> Result<BucketId, WordleError> {
+        if guess.len() != self.answer.len() {
+            return Err(WordleError::InvalidInput(format!(
+                "'{}' is not {} letters long",
+                guess,
+                self.answer.len()
+            )));
+        }
+        Ok(score_with_rules(self.rules, guess.as_bytes(), self.answer.as_bytes()))
+    }
+}
+
+// Asks for the feedback for each guess, e.g. from a player on stdin,
+// asking again until it parses.
+pub struct ReaderOracle<R, W> {
+    input: R,
+    output: W,
+}
+
+impl<R: BufRead, W: Write> ReaderOracle<R, W> {
+    pub fn new(input: R, output: W) -> ReaderOracle<R, W> {
+        ReaderOracle { input, output }
+    }
+}
+
+impl<R: BufRead, W: Write> Oracle for ReaderOracle<R, W> {
+    fn score(&mut self, guess: &str) -> Result<BucketId, WordleError> {
+        let io_error = |error| WordleError::Io {
+            path: "<input>".into(),
+            error,
+        };
+        loop {
+            write!(self.output, "Feedback for {}> ", guess).map_err(io_error)?;
+            self.output.flush().map_err(io_error)?;
+            let mut line = String::new();
+            if self.input.read_line(&mut line).map_err(io_error)? == 0 {
+                return Err(WordleError::InvalidInput(String::from("No feedback given")));
+            }
+            match parse_score(line.trim(), guess.len()) {
+                Ok(score) => return Ok(score),
+                Err(e) => writeln!(self.output, "{}", e).map_err(io_error)?,
+            }
+        }
+    }
+}
+
+// Plays like Absurdle: rather than picking an answer up front, gives
+// whatever feedback leaves the most candidates, so every guess's worst
+// case happens.
+pub struct Adversary<'a> {
+    s: &'a Scorer,
+    candidates: Vec<usize>,
+}
+
+impl<'a> Adversary<'a> {
+    pub fn new(s: &'a Scorer, candidates: Vec<usize>) -> Adversary<'a> {
+        Adversary { s, candidates }
+    }
+
+    // The answers consistent with the feedback given so far.
+    pub fn candidates(&self) -> &[usize] {
+        &self.candidates
+    }
+}
+
+impl<'a> Oracle for Adversary<'a> {
+    fn score(&mut self, guess: &str) -> Result<BucketId, WordleError> {
+        let s = self.s;
+        let guess = s.guess_index(guess).ok_or_else(|| WordleError::UnknownGuess {
+            word: guess.to_string(),
+        })?;
+        // bucket_answers puts the largest first.
+        let largest = s.bucket_answers(guess, &self.candidates).into_iter().next().ok_or_else(|| {
+            WordleError::InvalidInput(String::from("No candidate answers remain"))
+        })?;
+        let score = s.score(guess, largest[0]);
+        self.candidates = largest;
+        Ok(score)
+    }
+}
+
+// Passes on another oracle's feedback, but gets one tile wrong, as a
+// player entering it might: the tile at 'position' in the feedback for
+// guess number 'turn' (from 0) becomes one of the other two colours,
+// 'other' (0 or 1) picking which.
+pub struct NoisyOracle<O> {
+    inner: O,
+    turn: usize,
+    position: usize,
+    other: usize,
+    num_scored: usize,
+}
+
+impl<O: Oracle> NoisyOracle<O> {
+    pub fn new(inner: O, turn: usize, position: usize, other: usize) -> NoisyOracle<O> {
+        assert!(other < 2);
+        NoisyOracle {
+            inner,
+            turn,
+            position,
+            other,
+            num_scored: 0,
+        }
+    }
+}
+
+impl<O: Oracle> Oracle for NoisyOracle<O> {
+    fn score(&mut self, guess: &str) -> Result<BucketId, WordleError> {
+        let score = self.inner.score(guess)?;
+        self.num_scored += 1;
+        if self.num_scored != self.turn + 1 {
+            return Ok(score);
+        }
+        let mut scores = decode_score(score, guess.len());
+        let others = [CharScore::Absent, CharScore::Correct, CharScore::Present]
+            .iter()
+            .copied()
+            .filter(|cs| *cs != scores[self.position])
+            .collect::<Vec<_>>();
+        scores[self.position] = others[self.other];
+        Ok(encode_score(scores.iter().cloned()))
+    }
+}
+
+// Plays a Wordle clone over HTTP. Each guess is POSTed to the URL as
+// JSON, '{"guess": "crane"}', and the reply gives the (zero-based)
+// positions of the greens and yellows, e.g.
+// '{"greens": [0], "yellows": [2, 4]}'. Everything else is absent.
+#[cfg(feature = "http")]
+pub struct HttpOracle {
+    url: String,
+}
+
+#[cfg(feature = "http")]
+impl HttpOracle {
+    pub fn new(url: &str) -> HttpOracle {
+        HttpOracle { url: url.to_string() }
+    }
+
+    fn error(&self, message: impl ToString) -> WordleError {
+        WordleError::Http {
+            url: self.url.clone(),
+            message: message.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "http")]
+impl Oracle for HttpOracle {
+    fn score(&mut self, guess: &str) -> Result<BucketId, WordleError> {
+        let reply: serde_json::Value = ureq::post(&self.url)
+            .send_json(serde_json::json!({ "guess": guess }))
+            .map_err(|e| self.error(e))?
+            .into_json()
+            .map_err(|e| self.error(e))?;
+        parse_feedback(&reply, guess.len()).ok_or_else(|| self.error(format!("Unexpected reply {}", reply)))
+    }
+}
+
+#[cfg(feature = "http")]
+fn parse_feedback(reply: &serde_json::Value, len: usize) -> Option<BucketId> {
+    let mut scores = vec![CharScore::Absent; len];
+    for (key, cs) in [("greens", CharScore::Correct), ("yellows", CharScore::Present)] {
+        for pos in reply.get(key)?.as_array()? {
+            *scores.get_mut(pos.as_u64()? as usize)? = cs;
+        }
+    }
+    Some(encode_score(scores.iter().cloned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{small_scorer, WORD_LEN};
+
+    #[test]
+    fn test_known_answer() {
+        let mut oracle = KnownAnswer::new("coats", ScoringRules::Standard);
+        assert_eq!(oracle.score("spoon").unwrap(), parse_score("yayaa", WORD_LEN).unwrap());
+        assert!(oracle.score("spoons").is_err());
+    }
+
+    #[test]
+    fn test_reader_oracle() {
+        let mut output = Vec::new();
+        let mut oracle = ReaderOracle::new(&b"gggg\nyayaa\n"[..], &mut output);
+        assert_eq!(oracle.score("spoon").unwrap(), parse_score("yayaa", WORD_LEN).unwrap());
+        // Out of input.
+        assert!(oracle.score("spoon").is_err());
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("Feedback for spoon> "));
+        assert_eq!(output.matches("Feedback for").count(), 3);
+    }
+
+    #[test]
+    fn test_adversary() {
+        let s = small_scorer(&["zzzzb"], &["aaaaa", "aaaab", "aaaac"]);
+
+        // "zzzzb" only picks out "aaaab", so the adversary says it's not.
+        let mut oracle = Adversary::new(&s, vec![0, 1, 2]);
+        assert_eq!(oracle.score("zzzzb").unwrap(), parse_score("aaaaa", WORD_LEN).unwrap());
+        assert_eq!(oracle.candidates(), &[0, 2]);
+        assert!(oracle.score("xxxxx").is_err());
+    }
+
+    #[test]
+    fn test_noisy_oracle() {
+        let mut oracle = NoisyOracle::new(KnownAnswer::new("coats", ScoringRules::Standard), 1, 0, 1);
+        assert_eq!(oracle.score("spoon").unwrap(), parse_score("yayaa", WORD_LEN).unwrap());
+        // The yellow becomes the second of the others, green.
+        assert_eq!(oracle.score("spoon").unwrap(), parse_score("gayaa", WORD_LEN).unwrap());
+        assert_eq!(oracle.score("spoon").unwrap(), parse_score("yayaa", WORD_LEN).unwrap());
+
+        let mut oracle = NoisyOracle::new(KnownAnswer::new("coats", ScoringRules::Standard), 0, 0, 0);
+        assert_eq!(oracle.score("spoon").unwrap(), parse_score("aayaa", WORD_LEN).unwrap());
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_parse_feedback() {
+        let reply = serde_json::json!({ "greens": [0], "yellows": [2, 4] });
+        assert_eq!(parse_feedback(&reply, WORD_LEN), Some(parse_score("gayay", WORD_LEN).unwrap()));
+        assert_eq!(parse_feedback(&serde_json::json!({ "greens": [7], "yellows": [] }), WORD_LEN), None);
+        assert_eq!(parse_feedback(&serde_json::json!({ "greens": [] }), WORD_LEN), None);
+    }
+}
@@ -0,0 +1,108 @@
+//
+// Practice mode: the solver plays a game against a secret answer, and
+// the player works out the feedback for each of its guesses, to learn
+// how Wordle scores repeated letters and the like. The real feedback is
+// shown for any mistakes, and the game carries on with that.
+//
+
+use std::io::{self, BufRead, Write};
+
+use crate::{parse_score, render_score, GameState, Ranking, Scorer};
+
+// Play the solver's guesses (starting with the opener, if given) until
+// it finds the answer, the player enters "give-up", or the input runs
+// out, returning the number of wrong feedbacks given.
+pub fn practice(
+    s: &Scorer,
+    ranking: Ranking,
+    opener: Option<usize>,
+    candidates: Vec<usize>,
+    answer: usize,
+    mut input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<usize> {
+    let mut state = GameState::new(candidates);
+    let mut mistakes = 0;
+    writeln!(output, "Give the feedback for each guess, e.g. 'gyaag', or 'give-up' to see the answer.")?;
+    while !state.is_solved(s) {
+        let guess = match opener {
+            Some(opener) if state.history().is_empty() => opener,
+            _ => state.suggest(s, ranking).unwrap(),
+        };
+        let score = s.score(guess, answer);
+
+        let given = loop {
+            write!(output, "Guess {}: {}> ", state.history().len() + 1, s.guesses[guess])?;
+            output.flush()?;
+            let mut line = String::new();
+            if input.read_line(&mut line)? == 0 {
+                writeln!(output)?;
+                writeln!(output, "The answer was '{}'.", s.answers[answer])?;
+                return Ok(mistakes);
+            }
+            if line.trim() == "give-up" {
+                writeln!(
+                    output,
+                    "The answer was '{}', with {} candidates left.",
+                    s.answers[answer],
+                    state.candidates().len()
+                )?;
+                return Ok(mistakes);
+            }
+            match parse_score(line.trim(), s.word_len) {
+                Ok(given) => break given,
+                Err(e) => writeln!(output, "{}", e)?,
+            }
+        };
+        if given == score {
+            writeln!(output, "Correct: {}", render_score(score, s.word_len))?;
+        } else {
+            mistakes += 1;
+            writeln!(output, "Not quite: it's {}, not {}", render_score(score, s.word_len), render_score(given, s.word_len))?;
+        }
+        // The true feedback is always consistent.
+        state.apply(s, guess, score).unwrap();
+    }
+    writeln!(
+        output,
+        "Solved in {} guesses, with {} mistakes in the feedback.",
+        state.history().len(),
+        mistakes
+    )?;
+    Ok(mistakes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::small_scorer;
+
+    #[test]
+    fn test_practice() {
+        let s = small_scorer(&["zzzbc"], &["aaaaa", "aaaab", "aaaac"]);
+
+        // "zzzbc" against "aaaab" is "aaaya", leaving only the answer.
+        let input = "aaaga\nnonsense\nggggg\n";
+        let mut output = Vec::new();
+        let mistakes = practice(&s, Ranking::WorstCase, Some(0), vec![0, 1, 2], 1, input.as_bytes(), &mut output);
+        assert_eq!(mistakes.unwrap(), 1);
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Not quite: it's ⬛⬛⬛🟨⬛, not ⬛⬛⬛🟩⬛"));
+        assert!(output.contains("Guess 2: aaaab> "));
+        assert!(output.contains("Solved in 2 guesses, with 1 mistakes"));
+
+        // Giving up reveals the answer.
+        let mut output = Vec::new();
+        let mistakes = practice(&s, Ranking::WorstCase, Some(0), vec![0, 1, 2], 1, &b"give-up\n"[..], &mut output);
+        assert_eq!(mistakes.unwrap(), 0);
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("The answer was 'aaaab', with 3 candidates left."));
+        assert!(!output.contains("Solved"));
+
+        // As does running out of input.
+        let mut output = Vec::new();
+        let mistakes = practice(&s, Ranking::WorstCase, None, vec![0, 1, 2], 2, &b""[..], &mut output);
+        assert_eq!(mistakes.unwrap(), 0);
+        assert!(String::from_utf8(output).unwrap().contains("The answer was 'aaaac'."));
+    }
+}
@@ -0,0 +1,59 @@
+//
+// Small seeded random number generator (SplitMix64), so that sampled
+// runs are reproducible.
+//
+
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // A number in 0..n. Slightly biased for huge n, which doesn't
+    // matter for picking words.
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    // True with probability p.
+    pub fn chance(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+
+    // Pick k distinct items from the slice.
+    pub fn sample<T: Copy>(&mut self, items: &[T], k: usize) -> Vec<T> {
+        let mut items = items.to_vec();
+        let k = k.min(items.len());
+        for i in 0..k {
+            let j = i + self.below(items.len() - i);
+            items.swap(i, j);
+        }
+        items.truncate(k);
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample() {
+        let items = (0..10).collect::<Vec<usize>>();
+        let mut sample = Rng::new(42).sample(&items, 5);
+        assert_eq!(sample, Rng::new(42).sample(&items, 5));
+        sample.sort();
+        sample.dedup();
+        assert_eq!(sample.len(), 5);
+        assert_eq!(Rng::new(1).sample(&items, 20).len(), 10);
+    }
+}
@@ -0,0 +1,98 @@
+//
+// A line-based protocol over a game in progress, for editors and bots
+// that want to keep one process (and its score cache) around rather
+// than starting a new one for each query.
+//
+// Each line of input is a command, and gets a single line in reply,
+// starting "ok" or "error":
+//
+//  * "suggest": "ok <guess>", the best guess to make next.
+//  * "apply <guess> <feedback>": "ok <n>", narrowing the candidates to
+//    the n consistent with the feedback. Feedback is as for parse_score.
+//  * "reset": "ok <n>", starting a new game with all n candidates.
+//  * "candidates": "ok <answer> <answer> ...", those left.
+//
+// Blank lines are ignored, and the session ends with the input.
+//
+
+use std::io::{self, BufRead, Write};
+
+use crate::{parse_score, GameState, Ranking, Scorer, WordleError};
+
+pub fn serve(
+    s: &Scorer,
+    ranking: Ranking,
+    candidates: Vec<usize>,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<()> {
+    let mut state = GameState::new(candidates.clone());
+    for line in input.lines() {
+        let line = line?;
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        if words.is_empty() {
+            continue;
+        }
+        let reply = match words.as_slice() {
+            ["suggest"] => state
+                .suggest(s, ranking)
+                .map(|guess| s.guesses[guess].clone())
+                .ok_or_else(|| WordleError::InvalidInput(String::from("No candidate answers remain"))),
+            ["apply", word, feedback] => apply(s, &mut state, word, feedback),
+            ["reset"] => {
+                state = GameState::new(candidates.clone());
+                Ok(state.candidates().len().to_string())
+            }
+            ["candidates"] => Ok(state
+                .candidates()
+                .iter()
+                .map(|a| s.answers[*a].as_str())
+                .collect::<Vec<_>>()
+                .join(" ")),
+            _ => Err(WordleError::InvalidInput(format!(
+                "Unknown command '{}', expected 'suggest', 'apply <guess> <feedback>', 'reset' or 'candidates'",
+                line.trim()
+            ))),
+        };
+        match reply {
+            Ok(reply) if reply.is_empty() => writeln!(output, "ok")?,
+            Ok(reply) => writeln!(output, "ok {}", reply)?,
+            Err(e) => writeln!(output, "error {}", e)?,
+        }
+        output.flush()?;
+    }
+    Ok(())
+}
+
+// Apply feedback for a guess, returning how many candidates are left.
+fn apply(s: &Scorer, state: &mut GameState, word: &str, feedback: &str) -> Result<String, WordleError> {
+    let word = word.to_ascii_lowercase();
+    let guess = s.guess_index(&word).ok_or(WordleError::UnknownGuess { word })?;
+    state.apply(s, guess, parse_score(feedback, s.word_len)?)?;
+    Ok(state.candidates().len().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::small_scorer;
+
+    #[test]
+    fn test_session() {
+        let s = small_scorer(&["zzzbc"], &["aaaaa", "aaaab", "aaaac", "aaaad"]);
+
+        let input = "suggest\napply zzzbc aaaaa\n\ncandidates\nsuggest\napply zzzbc aaaag\n\
+                     apply qqqqq aaaaa\nbogus\nreset\ncandidates\n";
+        let mut output = Vec::new();
+        serve(&s, Ranking::WorstCase, vec![0, 1, 2, 3], input.as_bytes(), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 9);
+        assert_eq!(&lines[..4], &["ok zzzbc", "ok 2", "ok aaaaa aaaad", "ok aaaaa"]);
+        // "aaaac" has already been ruled out.
+        assert!(lines[4].starts_with("error No candidate answers"));
+        assert_eq!(lines[5], "error 'qqqqq' is not in the word list");
+        assert!(lines[6].starts_with("error Unknown command 'bogus'"));
+        assert_eq!(&lines[7..], &["ok 4", "ok aaaaa aaaab aaaac aaaad"]);
+    }
+}
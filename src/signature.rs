@@ -0,0 +1,110 @@
+//
+// Compact keys for sets of candidate answers, for memoising work on
+// them.
+//
+
+// SplitMix64's increment.
+const GOLDEN: u64 = 0x9e37_79b9_7f4a_7c15;
+
+// SplitMix64's output function, to spread bits around.
+fn mix(z: u64) -> u64 {
+    let z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    let z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+// A 64-bit hash of a set of candidates, independent of the order
+// they're given in. Collisions are unlikely, but possible, so callers
+// that can't tolerate them should use CandidateBitset instead.
+pub fn candidate_signature(candidates: &[usize]) -> u64 {
+    let mut sorted;
+    let candidates = if candidates.windows(2).all(|w| w[0] <= w[1]) {
+        candidates
+    } else {
+        sorted = candidates.to_vec();
+        sorted.sort_unstable();
+        &sorted
+    };
+    candidates.iter().fold(mix(candidates.len() as u64 ^ GOLDEN), |h, idx| {
+        mix(h.wrapping_add(GOLDEN) ^ *idx as u64)
+    })
+}
+
+// A 64-bit hash of a list of words, in the order given, e.g. to check a
+// file saved for one word list isn't used with another.
+pub fn word_list_signature<'a>(words: impl Iterator<Item = &'a str>) -> u64 {
+    words.fold(GOLDEN, |h, word| {
+        let h = word.bytes().fold(mix(h), |h, c| mix(h.wrapping_add(GOLDEN) ^ c as u64));
+        // End the word, so "ab", "c" differs from "a", "bc".
+        mix(h ^ word.len() as u64)
+    })
+}
+
+// An exact set of candidates, one bit per answer. For a few thousand
+// answers this is a few hundred bytes, and comparing or hashing it is
+// fast.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct CandidateBitset(Vec<u64>);
+
+impl CandidateBitset {
+    pub fn new(num_answers: usize, candidates: &[usize]) -> CandidateBitset {
+        let mut words = vec![0u64; num_answers.div_ceil(64)];
+        for idx in candidates.iter() {
+            words[idx / 64] |= 1 << (idx % 64);
+        }
+        CandidateBitset(words)
+    }
+
+    pub fn contains(&self, idx: usize) -> bool {
+        self.0.get(idx / 64).is_some_and(|w| w & (1 << (idx % 64)) != 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|w| *w == 0)
+    }
+
+    // The candidates, in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().enumerate().flat_map(|(word_idx, w)| {
+            (0..64).filter(move |bit| w & (1 << bit) != 0).map(move |bit| word_idx * 64 + bit)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidate_signature() {
+        assert_eq!(candidate_signature(&[1, 5, 70]), candidate_signature(&[70, 1, 5]));
+        assert_ne!(candidate_signature(&[1, 5, 70]), candidate_signature(&[1, 5, 71]));
+        assert_ne!(candidate_signature(&[1, 5]), candidate_signature(&[1, 5, 0]));
+        assert_ne!(candidate_signature(&[]), candidate_signature(&[0]));
+    }
+
+    #[test]
+    fn test_word_list_signature() {
+        let signature = |words: &[&str]| word_list_signature(words.iter().copied());
+        assert_eq!(signature(&["cigar", "rebut"]), signature(&["cigar", "rebut"]));
+        assert_ne!(signature(&["cigar", "rebut"]), signature(&["rebut", "cigar"]));
+        assert_ne!(signature(&["ab", "c"]), signature(&["a", "bc"]));
+        assert_ne!(signature(&[]), signature(&[""]));
+    }
+
+    #[test]
+    fn test_candidate_bitset() {
+        let set = CandidateBitset::new(200, &[70, 1, 5, 199]);
+        assert_eq!(set, CandidateBitset::new(200, &[1, 5, 70, 199]));
+        assert_ne!(set, CandidateBitset::new(200, &[1, 5, 70]));
+        assert!(set.contains(70) && !set.contains(71) && !set.contains(1000));
+        assert_eq!(set.len(), 4);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![1, 5, 70, 199]);
+        assert!(!set.is_empty());
+        assert!(CandidateBitset::new(200, &[]).is_empty());
+    }
+}
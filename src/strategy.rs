@@ -0,0 +1,104 @@
+//
+// A complete greedy strategy after a fixed opener: the guess to make
+// for every set of candidates that can come up, worked out once so
+// games can then be played (or the strategy written out) by lookup.
+//
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::{CandidateBitset, Ranking, Scorer};
+
+pub struct Strategy {
+    num_answers: usize,
+    // The guess for each reachable set of candidates.
+    guesses: HashMap<CandidateBitset, usize>,
+}
+
+impl Strategy {
+    // The guess to make with these candidates left, if the strategy
+    // can reach them.
+    pub fn guess_for(&self, candidates: &[usize]) -> Option<usize> {
+        self.guesses.get(&CandidateBitset::new(self.num_answers, candidates)).copied()
+    }
+
+    // The number of sets of candidates the strategy covers.
+    pub fn len(&self) -> usize {
+        self.guesses.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.guesses.is_empty()
+    }
+}
+
+impl Scorer {
+    // Open with 'opener', then guess greedily by the ranking, recording
+    // the guess for every set of candidates reached, a level of the
+    // game tree at a time. Sets reached by more than one path are only
+    // expanded once.
+    pub fn build_strategy(&self, opener: usize, candidates: &[usize], ranking: Ranking) -> Strategy {
+        let mut guesses = HashMap::new();
+        let mut queue = VecDeque::new();
+        let root = CandidateBitset::new(self.answers.len(), candidates);
+        guesses.insert(root, opener);
+        queue.push_back((candidates.to_vec(), opener));
+
+        while let Some((candidates, guess)) = queue.pop_front() {
+            for bucket in self.bucket_answers(guess, &candidates) {
+                // Nothing more to do once the guess is right.
+                if self.score(guess, bucket[0]) == self.correct_score() {
+                    continue;
+                }
+                let key = CandidateBitset::new(self.answers.len(), &bucket);
+                if guesses.contains_key(&key) {
+                    continue;
+                }
+                let next = self.best_guess(&bucket, ranking).unwrap();
+                guesses.insert(key, next);
+                queue.push_back((bucket, next));
+            }
+        }
+
+        Strategy {
+            num_answers: self.answers.len(),
+            guesses,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::small_scorer;
+
+    #[test]
+    fn test_build_strategy() {
+        let s = small_scorer(&["zzzzb", "zzzbc"], &["aaaaa", "aaaab", "aaaac"]);
+        let candidates = vec![0, 1, 2];
+        let idx = |w: &str| s.answers.iter().position(|a| a == w).unwrap();
+        let opener = s.guesses.iter().position(|g| g == "zzzzb").unwrap();
+        let strategy = s.build_strategy(opener, &candidates, Ranking::WorstCase);
+
+        // Playing every answer by the strategy only reaches sets it has
+        // a guess for, and solves them all.
+        for target in candidates.iter() {
+            let mut left = candidates.clone();
+            let mut num_guesses = 0;
+            loop {
+                let guess = strategy.guess_for(&left).unwrap();
+                num_guesses += 1;
+                if s.guesses[guess] == s.answers[*target] {
+                    break;
+                }
+                left = s.filter_candidates(guess, s.score(guess, *target), &left);
+                assert!(num_guesses < 4);
+            }
+        }
+
+        // The opener splits off aaaab, so aaaab and aaaac are never
+        // left together.
+        assert_eq!(strategy.guess_for(&candidates), Some(opener));
+        assert!(strategy.guess_for(&[idx("aaaaa"), idx("aaaac")]).is_some());
+        assert!(strategy.guess_for(&[idx("aaaab"), idx("aaaac")]).is_none());
+    }
+}
@@ -0,0 +1,64 @@
+//
+// Play random games on small alphabets, where repeated letters are
+// common, checking GameState stays consistent after every step.
+//
+
+use proptest::prelude::*;
+use wordle_solver::{num_buckets, BucketId, GameState, Scorer, WORD_LEN};
+
+fn scorer(guesses: Vec<String>, answers: Vec<String>) -> Scorer {
+    Scorer::from_word_lists(guesses, answers).unwrap()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(500))]
+
+    // With the true feedback for a fixed answer, the candidates never
+    // grow, and always include the answer.
+    #[test]
+    fn test_true_feedback(
+        answers in prop::collection::btree_set("[a-c]{5}", 2..30),
+        guesses in prop::collection::vec("[a-d]{5}", 1..10),
+        target in any::<prop::sample::Index>(),
+        moves in prop::collection::vec(any::<prop::sample::Index>(), 1..8),
+    ) {
+        let s = scorer(guesses, answers.into_iter().collect());
+        let target = target.index(s.answers.len());
+        let mut state = GameState::new((0..s.answers.len()).collect());
+        for guess in moves.iter().map(|m| m.index(s.guesses.len())) {
+            let before = state.candidates().to_vec();
+            state.apply(&s, guess, s.score(guess, target)).unwrap();
+            prop_assert!(state.candidates().len() <= before.len());
+            prop_assert!(state.candidates().iter().all(|a| before.contains(a)));
+            prop_assert!(state.candidates().contains(&target));
+        }
+    }
+
+    // With arbitrary feedback, impossible feedback is rejected and
+    // leaves the state alone, and anything else leaves a non-empty
+    // subset of the candidates.
+    #[test]
+    fn test_random_feedback(
+        answers in prop::collection::btree_set("[a-c]{5}", 2..30),
+        guesses in prop::collection::vec("[a-d]{5}", 1..10),
+        moves in prop::collection::vec((any::<prop::sample::Index>(), 0..num_buckets(WORD_LEN)), 1..8),
+    ) {
+        let s = scorer(guesses, answers.into_iter().collect());
+        let mut state = GameState::new((0..s.answers.len()).collect());
+        for (guess, score) in moves.iter().map(|(m, score)| (m.index(s.guesses.len()), *score as BucketId)) {
+            let before = state.candidates().to_vec();
+            let possible = before.iter().any(|a| s.score(guess, *a) == score);
+            match state.apply(&s, guess, score) {
+                Ok(()) => {
+                    prop_assert!(possible);
+                    prop_assert!(!state.candidates().is_empty());
+                    prop_assert!(state.candidates().iter().all(|a| before.contains(a)));
+                }
+                Err(_) => {
+                    prop_assert!(!possible);
+                    prop_assert_eq!(state.candidates(), &before[..]);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,27 @@
+//
+// Running without the word lists should explain how to provide them,
+// rather than panicking.
+//
+
+use std::process::Command;
+
+#[test]
+fn test_missing_word_lists() {
+    let dir = std::env::temp_dir().join(format!("wordle-empty-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_wordle-solver"))
+        .arg("--self-solve")
+        .current_dir(&dir)
+        .env_remove("WORDLE_WORDS_DIR")
+        .output()
+        .unwrap();
+    std::fs::remove_dir(&dir).unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Could not find the word list 'words/possible_guesses.txt'"));
+    assert!(stderr.contains("WORDLE_WORDS_DIR"));
+    assert!(stderr.contains("--guesses and --solutions"));
+    assert!(!stderr.contains("panicked"));
+}
@@ -0,0 +1,58 @@
+//
+// Check score_wordle against a slow but obviously correct scorer, on
+// random words over a small alphabet, so repeated letters are common.
+//
+
+use std::collections::HashMap;
+
+use proptest::prelude::*;
+use wordle_solver::{encode_score, score_wordle, CharScore};
+
+// Greens first. Then each other guessed letter, left to right, is
+// yellow if the answer has a copy of it left over that isn't green or
+// already used for an earlier yellow.
+fn reference_score(guess: &str, answer: &str) -> Vec<CharScore> {
+    let guess = guess.chars().collect::<Vec<_>>();
+    let answer = answer.chars().collect::<Vec<_>>();
+
+    let mut unused: HashMap<char, usize> = HashMap::new();
+    for (g, a) in guess.iter().zip(answer.iter()) {
+        if g != a {
+            *unused.entry(*a).or_insert(0) += 1;
+        }
+    }
+
+    guess
+        .iter()
+        .zip(answer.iter())
+        .map(|(g, a)| {
+            if g == a {
+                return CharScore::Correct;
+            }
+            match unused.get_mut(g) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    CharScore::Present
+                }
+                _ => CharScore::Absent,
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn test_reference_examples() {
+    use CharScore::{Absent as A, Correct as C, Present as P};
+    assert_eq!(reference_score("spoon", "coats"), vec![P, A, P, A, A]);
+    assert_eq!(reference_score("eerie", "there"), vec![P, A, P, A, C]);
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10_000))]
+
+    #[test]
+    fn test_matches_reference(guess in "[a-e]{5}", answer in "[a-e]{5}") {
+        let expected = encode_score(reference_score(&guess, &answer).into_iter());
+        prop_assert_eq!(score_wordle(guess.as_bytes(), answer.as_bytes()), expected);
+    }
+}
@@ -0,0 +1,49 @@
+//
+// The original scoring tests, written against the public API only.
+//
+
+use wordle_solver::{encode_score, score_wordle, CharScore};
+
+const A: CharScore = CharScore::Absent;
+const C: CharScore = CharScore::Correct;
+const P: CharScore = CharScore::Present;
+
+fn check(guess: &str, answer: &str, score: &[CharScore]) {
+    assert_eq!(
+        score_wordle(guess.as_bytes(), answer.as_bytes()),
+        encode_score(score.iter().cloned())
+    );
+}
+
+#[test]
+fn test_simple_green() {
+    check("weary", "wills", &[C, A, A, A, A]);
+}
+
+#[test]
+fn test_simple_yellow() {
+    check("pilot", "leaks", &[A, A, P, A, A]);
+}
+
+#[test]
+fn test_double_yellow() {
+    check("kazoo", "tools", &[A, A, A, P, P]);
+}
+
+#[test]
+fn test_green_overrides_yellow() {
+    // Letters are 'used up' by exact matches.
+    check("loose", "chore", &[A, A, C, A, C]);
+}
+
+#[test]
+fn test_yellow_overrides_yellow() {
+    // Letters are 'used up' by inexact matches, too.
+    // So, only one 'O' matches.
+    check("spoon", "coats", &[P, A, P, A, A]);
+}
+
+#[test]
+fn test_success() {
+    check("prize", "prize", &[C, C, C, C, C]);
+}
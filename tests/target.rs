@@ -0,0 +1,33 @@
+//
+// Playing against a word that isn't a possible answer should end in a
+// report that it can't be solved, rather than an error.
+//
+
+use std::process::Command;
+
+#[test]
+fn test_target_not_an_answer() {
+    let dir = std::env::temp_dir().join(format!("wordle-target-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("guesses.txt"), "zzzbc\n").unwrap();
+    std::fs::write(dir.join("solutions.txt"), "aaaaa\naaaab\naaaac\n").unwrap();
+
+    let run = |target: &str| {
+        Command::new(env!("CARGO_BIN_EXE_wordle-solver"))
+            .args(["--guesses", "guesses.txt", "--solutions", "solutions.txt", "--target", target])
+            .current_dir(&dir)
+            .output()
+            .unwrap()
+    };
+    let solved = run("aaaab");
+    let unsolvable = run("zzzzz");
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(solved.status.code(), Some(0));
+    assert!(String::from_utf8(solved.stdout).unwrap().ends_with("Solved in 2 guesses\n"));
+
+    assert_eq!(unsolvable.status.code(), Some(1));
+    let stdout = String::from_utf8(unsolvable.stdout).unwrap();
+    assert!(stdout.starts_with("zzzbc "));
+    assert!(stdout.contains("Unsolvable: no candidate answer gives that feedback"));
+}